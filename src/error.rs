@@ -5,15 +5,15 @@ use pinocchio::program_error::ProgramError;
 pub enum HateFunError {
     /// Combined fees exceed maximum of 20%
     FeesTooHigh = 0,
-    /// Creator address must be different from address A and B
+    /// Creator address must be different from every side address
     CreatorMustBeDifferent = 1,
     /// Minimum increase must be between 1% and 50%
     InvalidMinimumIncrease = 2,
-    /// Initial last swap must be at least 0.0001 SOL
+    /// Initial last swap is below the bucket's minimum initial swap
     InitialSwapTooLow = 3,
     /// Escrow balance is below required threshold
     InsufficientEscrowBalance = 4,
-    /// Cannot claim payout before 3 epochs have passed
+    /// Cannot claim payout before the claim delay has passed since the last flip
     ClaimTooEarly = 5,
     /// Only creator can close the bucket
     UnauthorizedClose = 6,
@@ -21,18 +21,343 @@ pub enum HateFunError {
     BucketHasFlips = 7,
     /// Cannot close bucket with non-empty escrows
     EscrowsNotEmpty = 8,
-    /// Invalid escrow account provided
+    /// Escrow account is not one of this bucket's escrows
     InvalidEscrow = 9,
     /// Arithmetic overflow
     Overflow = 10,
-    /// Deposit amount is too small (below minimum)
+    /// Deposit amount is below the bucket's minimum deposit
     DepositTooSmall = 11,
     /// Zero amount deposit not allowed
     ZeroAmountDeposit = 12,
+    /// Flushed escrow belongs to the side that already controls the bucket
+    SideAlreadyInControl = 13,
+    /// Bucket has already been settled; no further deposits, flushes or claims
+    BucketSettled = 14,
+    /// Bucket has been closed by its creator
+    BucketClosed = 15,
+    /// Bucket account holds an unknown status value
+    InvalidBucketStatus = 16,
+    /// Bucket account is not owned by this program
+    InvalidBucketOwner = 17,
+    /// Bucket account data has the wrong size
+    InvalidBucketSize = 18,
+    /// Account data does not start with the bucket discriminator
+    InvalidBucketDiscriminator = 19,
+    /// Bucket account uses an unsupported layout version
+    UnsupportedBucketVersion = 20,
+    /// Bucket account has already been initialized
+    BucketAlreadyInitialized = 21,
+    /// Side selector must be below the bucket's side_count (0 = A, 1 = B, up to 15)
+    InvalidSide = 22,
+    /// Claim delay unit is unknown or the delay is out of bounds for that unit
+    InvalidClaimDelay = 23,
+    /// Minimum deposit is below the program-wide floor of 1,000 lamports
+    InvalidMinimumDeposit = 24,
+    /// Minimum initial swap is below the program-wide floor of 0.0001 SOL
+    InvalidMinimumInitialSwap = 25,
+    /// Bucket account does not match the PDA derived from creator and seed
+    InvalidBucketAddress = 26,
+    /// Main bucket account does not match the bucket's main PDA
+    InvalidMainBucket = 27,
+    /// Escrow A account does not match the bucket's escrow A PDA
+    InvalidEscrowA = 28,
+    /// Escrow B account does not match the bucket's escrow B PDA
+    InvalidEscrowB = 29,
+    /// Creator account does not match the bucket's creator address
+    InvalidCreator = 30,
+    /// Winner account does not match the bucket's current target
+    InvalidWinner = 31,
+    /// Escrow account is no longer owned by the program
+    EscrowNotInitialized = 32,
+    /// Bucket last_swap differs from the caller's expected value
+    LastSwapMismatch = 33,
+    /// Bucket current_target differs from the caller's expected value
+    CurrentTargetMismatch = 34,
+    /// Deposit exceeds the caller's maximum lamports
+    MaxLamportsExceeded = 35,
+    /// Only the side in control can reinforce
+    SideNotInControl = 36,
+    /// Bucket must have between 2 and 16 sides
+    InvalidSideCount = 37,
+    /// Two sides share the same address
+    DuplicateSideAddress = 38,
+    /// Deposit record account is not the depositor's record for this bucket and side
+    InvalidDepositRecord = 39,
+    /// Refund policy must be 0 (sweep) or 1 (refund)
+    InvalidRefundPolicy = 40,
+    /// Bucket sweeps pending escrow balances instead of refunding them
+    RefundsDisabled = 41,
+    /// Bucket is not settled
+    BucketNotSettled = 42,
+    /// Deposit record has no pending lamports to refund
+    NothingToRefund = 43,
+    /// Withdrawing pending deposits is disabled for this bucket
+    WithdrawalsDisabled = 44,
+    /// No pending deposit is left to withdraw
+    NothingToWithdraw = 45,
+    /// Withdrawal exceeds the pending deposit or the escrow balance
+    WithdrawTooLarge = 46,
+    /// Payout mode must be 0 (winner) or 1 (proportional)
+    InvalidPayoutMode = 47,
+    /// This deposit record already claimed its share
+    ShareAlreadyClaimed = 48,
+    /// Deposit record is not for the winning side
+    NotWinningSide = 49,
+    /// Bucket has no proportional payout to claim
+    NoShareToClaim = 50,
+    /// Side recipient weights must be 1-8 non-zero entries summing to 10000 bps
+    InvalidSideWeights = 51,
+    /// Side recipients must be distinct
+    DuplicateRecipient = 52,
+    /// Side configs can only be set in the bucket's creation slot, before any deposit
+    SideConfigLocked = 53,
+    /// Side config account is not the side's config PDA for this bucket
+    InvalidSideConfig = 54,
+    /// Recipient account does not match the side config
+    InvalidRecipient = 55,
+    /// Settlement account is invalid or does not belong to this bucket
+    InvalidSettlement = 56,
+    /// Payee has no unwithdrawn entry in this settlement
+    NothingToCollect = 57,
+    /// Creator must sign create_bucket
+    CreatorMustSign = 58,
+    /// Payer account does not match the bucket's recorded payer
+    InvalidPayer = 59,
+    /// Starting side must be a side index or random
+    InvalidStartingSide = 60,
+    /// Withdraw destination cannot be the settlement itself
+    InvalidDestination = 61,
+    /// Settled bucket still owes refunds or shares
+    SettlementNotDrained = 62,
+    /// Bucket cannot be closed in the slot it was created
+    BucketTooNew = 63,
+    /// Deposit record has no flushed contribution to share in
+    NoFlushedContribution = 64,
+    /// Side already has a config for this bucket
+    SideAlreadyConfigured = 65,
+    /// Side config was set for an earlier bucket at the same address
+    StaleSideConfig = 66,
+    /// Side config account is not an initialized side config of the current layout
+    SideConfigNotInitialized = 67,
+    /// Deposit record still holds a pending deposit or an unclaimed share
+    DepositRecordInUse = 68,
 }
 
+impl HateFunError {
+    /// Escrow mismatch error for a side index
+    pub fn invalid_escrow_for_side(side: u8) -> Self {
+        match side {
+            0 => HateFunError::InvalidEscrowA,
+            1 => HateFunError::InvalidEscrowB,
+            _ => HateFunError::InvalidEscrow,
+        }
+    }
+
+    /// Decode a `ProgramError::Custom(code)` value back into a HateFunError
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(HateFunError::FeesTooHigh),
+            1 => Some(HateFunError::CreatorMustBeDifferent),
+            2 => Some(HateFunError::InvalidMinimumIncrease),
+            3 => Some(HateFunError::InitialSwapTooLow),
+            4 => Some(HateFunError::InsufficientEscrowBalance),
+            5 => Some(HateFunError::ClaimTooEarly),
+            6 => Some(HateFunError::UnauthorizedClose),
+            7 => Some(HateFunError::BucketHasFlips),
+            8 => Some(HateFunError::EscrowsNotEmpty),
+            9 => Some(HateFunError::InvalidEscrow),
+            10 => Some(HateFunError::Overflow),
+            11 => Some(HateFunError::DepositTooSmall),
+            12 => Some(HateFunError::ZeroAmountDeposit),
+            13 => Some(HateFunError::SideAlreadyInControl),
+            14 => Some(HateFunError::BucketSettled),
+            15 => Some(HateFunError::BucketClosed),
+            16 => Some(HateFunError::InvalidBucketStatus),
+            17 => Some(HateFunError::InvalidBucketOwner),
+            18 => Some(HateFunError::InvalidBucketSize),
+            19 => Some(HateFunError::InvalidBucketDiscriminator),
+            20 => Some(HateFunError::UnsupportedBucketVersion),
+            21 => Some(HateFunError::BucketAlreadyInitialized),
+            22 => Some(HateFunError::InvalidSide),
+            23 => Some(HateFunError::InvalidClaimDelay),
+            24 => Some(HateFunError::InvalidMinimumDeposit),
+            25 => Some(HateFunError::InvalidMinimumInitialSwap),
+            26 => Some(HateFunError::InvalidBucketAddress),
+            27 => Some(HateFunError::InvalidMainBucket),
+            28 => Some(HateFunError::InvalidEscrowA),
+            29 => Some(HateFunError::InvalidEscrowB),
+            30 => Some(HateFunError::InvalidCreator),
+            31 => Some(HateFunError::InvalidWinner),
+            32 => Some(HateFunError::EscrowNotInitialized),
+            33 => Some(HateFunError::LastSwapMismatch),
+            34 => Some(HateFunError::CurrentTargetMismatch),
+            35 => Some(HateFunError::MaxLamportsExceeded),
+            36 => Some(HateFunError::SideNotInControl),
+            37 => Some(HateFunError::InvalidSideCount),
+            38 => Some(HateFunError::DuplicateSideAddress),
+            39 => Some(HateFunError::InvalidDepositRecord),
+            40 => Some(HateFunError::InvalidRefundPolicy),
+            41 => Some(HateFunError::RefundsDisabled),
+            42 => Some(HateFunError::BucketNotSettled),
+            43 => Some(HateFunError::NothingToRefund),
+            44 => Some(HateFunError::WithdrawalsDisabled),
+            45 => Some(HateFunError::NothingToWithdraw),
+            46 => Some(HateFunError::WithdrawTooLarge),
+            47 => Some(HateFunError::InvalidPayoutMode),
+            48 => Some(HateFunError::ShareAlreadyClaimed),
+            49 => Some(HateFunError::NotWinningSide),
+            50 => Some(HateFunError::NoShareToClaim),
+            51 => Some(HateFunError::InvalidSideWeights),
+            52 => Some(HateFunError::DuplicateRecipient),
+            53 => Some(HateFunError::SideConfigLocked),
+            54 => Some(HateFunError::InvalidSideConfig),
+            55 => Some(HateFunError::InvalidRecipient),
+            56 => Some(HateFunError::InvalidSettlement),
+            57 => Some(HateFunError::NothingToCollect),
+            58 => Some(HateFunError::CreatorMustSign),
+            59 => Some(HateFunError::InvalidPayer),
+            60 => Some(HateFunError::InvalidStartingSide),
+            61 => Some(HateFunError::InvalidDestination),
+            62 => Some(HateFunError::SettlementNotDrained),
+            63 => Some(HateFunError::BucketTooNew),
+            64 => Some(HateFunError::NoFlushedContribution),
+            65 => Some(HateFunError::SideAlreadyConfigured),
+            66 => Some(HateFunError::StaleSideConfig),
+            67 => Some(HateFunError::SideConfigNotInitialized),
+            68 => Some(HateFunError::DepositRecordInUse),
+            _ => None,
+        }
+    }
+
+    /// Decode a ProgramError returned by this program
+    pub fn from_program_error(error: &ProgramError) -> Option<Self> {
+        match error {
+            ProgramError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+}
+
+/// Human-readable messages for host-side tooling (kept out of the on-chain binary)
+#[cfg(not(target_os = "solana"))]
+impl HateFunError {
+    /// Description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            HateFunError::FeesTooHigh => "Combined fees exceed maximum of 20%",
+            HateFunError::CreatorMustBeDifferent => "Creator address must be different from every side address",
+            HateFunError::InvalidMinimumIncrease => "Minimum increase must be between 1% and 50%",
+            HateFunError::InitialSwapTooLow => "Initial last swap is below the bucket's minimum initial swap",
+            HateFunError::InsufficientEscrowBalance => "Escrow balance is below required threshold",
+            HateFunError::ClaimTooEarly => "Cannot claim payout before the claim delay has passed since the last flip",
+            HateFunError::UnauthorizedClose => "Only creator can close the bucket",
+            HateFunError::BucketHasFlips => "Cannot close bucket after first flip",
+            HateFunError::EscrowsNotEmpty => "Cannot close bucket with non-empty escrows",
+            HateFunError::InvalidEscrow => "Escrow account is not one of this bucket's escrows",
+            HateFunError::Overflow => "Arithmetic overflow",
+            HateFunError::DepositTooSmall => "Deposit amount is below the bucket's minimum deposit",
+            HateFunError::ZeroAmountDeposit => "Zero amount deposit not allowed",
+            HateFunError::SideAlreadyInControl => "Flushed escrow belongs to the side that already controls the bucket",
+            HateFunError::BucketSettled => "Bucket has already been settled; no further deposits, flushes or claims",
+            HateFunError::BucketClosed => "Bucket has been closed by its creator",
+            HateFunError::InvalidBucketStatus => "Bucket account holds an unknown status value",
+            HateFunError::InvalidBucketOwner => "Bucket account is not owned by this program",
+            HateFunError::InvalidBucketSize => "Bucket account data has the wrong size",
+            HateFunError::InvalidBucketDiscriminator => "Account data does not start with the bucket discriminator",
+            HateFunError::UnsupportedBucketVersion => "Bucket account uses an unsupported layout version",
+            HateFunError::BucketAlreadyInitialized => "Bucket account has already been initialized",
+            HateFunError::InvalidSide => "Side selector must be below the bucket's side_count (0 = A, 1 = B, up to 15)",
+            HateFunError::InvalidClaimDelay => "Claim delay unit is unknown or the delay is out of bounds for that unit",
+            HateFunError::InvalidMinimumDeposit => "Minimum deposit is below the program-wide floor of 1,000 lamports",
+            HateFunError::InvalidMinimumInitialSwap => "Minimum initial swap is below the program-wide floor of 0.0001 SOL",
+            HateFunError::InvalidBucketAddress => "Bucket account does not match the PDA derived from creator and seed",
+            HateFunError::InvalidMainBucket => "Main bucket account does not match the bucket's main PDA",
+            HateFunError::InvalidEscrowA => "Escrow A account does not match the bucket's escrow A PDA",
+            HateFunError::InvalidEscrowB => "Escrow B account does not match the bucket's escrow B PDA",
+            HateFunError::InvalidCreator => "Creator account does not match the bucket's creator address",
+            HateFunError::InvalidWinner => "Winner account does not match the bucket's current target",
+            HateFunError::EscrowNotInitialized => "Escrow account is no longer owned by the program",
+            HateFunError::LastSwapMismatch => "Bucket last_swap differs from the caller's expected value",
+            HateFunError::CurrentTargetMismatch => "Bucket current_target differs from the caller's expected value",
+            HateFunError::MaxLamportsExceeded => "Deposit exceeds the caller's maximum lamports",
+            HateFunError::SideNotInControl => "Only the side in control can reinforce",
+            HateFunError::InvalidSideCount => "Bucket must have between 2 and 16 sides",
+            HateFunError::DuplicateSideAddress => "Two sides share the same address",
+            HateFunError::InvalidDepositRecord => "Deposit record account is not the depositor's record for this bucket and side",
+            HateFunError::InvalidRefundPolicy => "Refund policy must be 0 (sweep) or 1 (refund)",
+            HateFunError::RefundsDisabled => "Bucket sweeps pending escrow balances instead of refunding them",
+            HateFunError::BucketNotSettled => "Bucket is not settled",
+            HateFunError::NothingToRefund => "Deposit record has no pending lamports to refund",
+            HateFunError::WithdrawalsDisabled => "Withdrawing pending deposits is disabled for this bucket",
+            HateFunError::NothingToWithdraw => "No pending deposit is left to withdraw",
+            HateFunError::WithdrawTooLarge => "Withdrawal exceeds the pending deposit or the escrow balance",
+            HateFunError::InvalidPayoutMode => "Payout mode must be 0 (winner) or 1 (proportional)",
+            HateFunError::ShareAlreadyClaimed => "This deposit record already claimed its share",
+            HateFunError::NotWinningSide => "Deposit record is not for the winning side",
+            HateFunError::NoShareToClaim => "Bucket has no proportional payout to claim",
+            HateFunError::InvalidSideWeights => "Side recipient weights must be 1-8 non-zero entries summing to 10000 bps",
+            HateFunError::DuplicateRecipient => "Side recipients must be distinct",
+            HateFunError::SideConfigLocked => "Side configs can only be set in the bucket's creation slot, before any deposit",
+            HateFunError::InvalidSideConfig => "Side config account is not the side's config PDA for this bucket",
+            HateFunError::InvalidRecipient => "Recipient account does not match the side config",
+            HateFunError::InvalidSettlement => "Settlement account is invalid or does not belong to this bucket",
+            HateFunError::NothingToCollect => "Payee has no unwithdrawn entry in this settlement",
+            HateFunError::CreatorMustSign => "Creator must sign create_bucket",
+            HateFunError::InvalidPayer => "Payer account does not match the bucket's recorded payer",
+            HateFunError::InvalidStartingSide => "Starting side must be a side index or random",
+            HateFunError::InvalidDestination => "Withdraw destination cannot be the settlement itself",
+            HateFunError::SettlementNotDrained => "Settled bucket still owes refunds or shares",
+            HateFunError::BucketTooNew => "Bucket cannot be closed in the slot it was created",
+            HateFunError::NoFlushedContribution => "Deposit record has no flushed contribution to share in",
+            HateFunError::SideAlreadyConfigured => "Side already has a config for this bucket",
+            HateFunError::StaleSideConfig => "Side config was set for an earlier bucket at the same address",
+            HateFunError::SideConfigNotInitialized => "Side config account is not an initialized side config of the current layout",
+            HateFunError::DepositRecordInUse => "Deposit record still holds a pending deposit or an unclaimed share",
+        }
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+impl core::fmt::Display for HateFunError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+impl std::error::Error for HateFunError {}
+
 impl From<HateFunError> for ProgramError {
     fn from(e: HateFunError) -> Self {
         ProgramError::Custom(e as u32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code_round_trip() {
+        for code in 0..=68 {
+            let error = HateFunError::from_code(code).expect("every code up to the last variant decodes");
+            assert_eq!(error as u32, code);
+            assert_eq!(HateFunError::from_program_error(&error.into()), Some(error));
+            assert!(!error.to_string().is_empty());
+        }
+        assert_eq!(HateFunError::from_code(69), None);
+    }
+
+    #[test]
+    fn test_from_program_error_ignores_builtin_errors() {
+        assert_eq!(HateFunError::from_program_error(&ProgramError::InvalidSeeds), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            HateFunError::InvalidWinner.to_string(),
+            "Winner account does not match the bucket's current target"
+        );
+    }
+}
@@ -2,34 +2,36 @@ use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvars::{clock::Clock, Sysvar},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
 use crate::{
     error::HateFunError,
-    state::{Bucket, pda},
-    verification::{calculate_payout_distribution, sum_balances},
+    state::{Bucket, BucketAction, BucketStatus, PayoutMode, RefundPolicy, SideConfig, pda},
+    verification::{
+        calculate_flushable_balance, calculate_payout_distribution, calculate_weighted_split,
+        effective_status, next_status, sum_balances, MAX_SIDES, MAX_SIDE_RECIPIENTS,
+    },
 };
+use super::{close_account, verify_extra_escrows};
 
 /// ClaimPayout instruction has no additional data
+/// Accounts: bucket, main bucket, escrow A, escrow B, creator, claimer, winner,
+/// payer (receives the rent reserves of the closed PDAs), then the escrows of sides 2 and up in side order,
+/// then, if the winning side has a SideConfig, that config followed by its recipients in order
 pub fn process_claim_payout(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     _data: &[u8],
 ) -> ProgramResult {
     // Parse accounts
-    let [bucket_account, main_bucket, escrow_a, escrow_b, creator, claimer, winner] = accounts else {
+    let [bucket_account, main_bucket, escrow_a, escrow_b, creator, claimer, winner, payer, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Verify bucket is owned by program
-    if bucket_account.owner() != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
-
-    // Load bucket state
-    let bucket = Bucket::from_account_info_unchecked(bucket_account)?;
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
 
     // Verify signer is claimer
     if !claimer.is_signer() {
@@ -37,48 +39,224 @@ pub fn process_claim_payout(
     }
 
     // Verify PDAs
-    let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
-    if main_bucket.key() != &main_bucket_pda {
-        return Err(ProgramError::InvalidSeeds);
+    let pot = PotAccounts::verify(program_id, bucket_account, main_bucket, escrow_a, escrow_b, bucket.side_count, remaining)?;
+    let side_config_accounts = &remaining[pot.extra_escrows.len()..];
+
+    // Verify creator address
+    if creator.key() != &bucket.creator_address {
+        return Err(HateFunError::InvalidCreator.into());
     }
 
-    let (escrow_a_pda, _) = pda::derive_escrow_a_address(bucket_account.key(), program_id);
-    if escrow_a.key() != &escrow_a_pda {
-        return Err(ProgramError::InvalidSeeds);
+    // Verify winner address
+    if winner.key() != &bucket.current_target {
+        return Err(HateFunError::InvalidWinner.into());
     }
 
-    let (escrow_b_pda, _) = pda::derive_escrow_b_address(bucket_account.key(), program_id);
-    if escrow_b.key() != &escrow_b_pda {
-        return Err(ProgramError::InvalidSeeds);
+    // Verify payer address
+    if payer.key() != &bucket.payer {
+        return Err(HateFunError::InvalidPayer.into());
     }
 
-    // Verify creator address
-    if creator.key() != &bucket.creator_address {
-        return Err(ProgramError::InvalidAccountData);
+    let payout = collect_payout(program_id, &pot, bucket, extra_sides, side_config_accounts.first())?;
+
+    // Coalition recipients follow the side config, in the config's order
+    let recipient_accounts = match payout.coalition {
+        Some((recipients, _)) => {
+            let recipient_accounts = side_config_accounts
+                .get(1..1 + recipients.len())
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            for (account, recipient) in recipient_accounts.iter().zip(recipients) {
+                if account.key() != recipient {
+                    return Err(HateFunError::InvalidRecipient.into());
+                }
+            }
+            recipient_accounts
+        }
+        None => &[],
+    };
+
+    // Distribute from bucket account
+    // SAFETY: These unsafe operations are justified because:
+    // 1. collect_payout validated the bucket holds the whole pot
+    // 2. Kani proof guarantees creator_cut + claimer_cut + winner_cut = total (no underflow)
+    // 3. The rent refund was collected on top of total
+    // 4. The transaction is atomic - either all distributions succeed or none do
+    unsafe {
+        *bucket_account.borrow_mut_lamports_unchecked() -= payout.rent_refund;
+        *payer.borrow_mut_lamports_unchecked() += payout.rent_refund;
+
+        *bucket_account.borrow_mut_lamports_unchecked() -= payout.creator_cut;
+        *creator.borrow_mut_lamports_unchecked() += payout.creator_cut;
+
+        *bucket_account.borrow_mut_lamports_unchecked() -= payout.claimer_cut;
+        *claimer.borrow_mut_lamports_unchecked() += payout.claimer_cut;
+
+        if let Some((_, recipient_cuts)) = payout.coalition {
+            for (recipient, &cut) in recipient_accounts.iter().zip(&recipient_cuts) {
+                *bucket_account.borrow_mut_lamports_unchecked() -= cut;
+                *recipient.borrow_mut_lamports_unchecked() += cut;
+            }
+        } else if !payout.pay_shares {
+            *bucket_account.borrow_mut_lamports_unchecked() -= payout.winner_cut;
+            *winner.borrow_mut_lamports_unchecked() += payout.winner_cut;
+        }
     }
 
-    // Verify winner address
-    if winner.key() != &bucket.current_target {
-        return Err(ProgramError::InvalidAccountData);
+    finish_payout(&pot, bucket, &payout)
+}
+
+/// Bucket PDAs a payout collects from, verified against the bucket's address
+pub(crate) struct PotAccounts<'a> {
+    pub bucket: &'a AccountInfo,
+    pub main_bucket: &'a AccountInfo,
+    pub escrow_a: &'a AccountInfo,
+    pub escrow_b: &'a AccountInfo,
+    pub extra_escrows: &'a [AccountInfo],
+}
+
+impl<'a> PotAccounts<'a> {
+    /// Verify the main bucket and escrow PDAs; the escrows of sides 2 and up are taken
+    /// from the front of `remaining`
+    pub fn verify(
+        program_id: &Pubkey,
+        bucket: &'a AccountInfo,
+        main_bucket: &'a AccountInfo,
+        escrow_a: &'a AccountInfo,
+        escrow_b: &'a AccountInfo,
+        side_count: u8,
+        remaining: &'a [AccountInfo],
+    ) -> Result<Self, ProgramError> {
+        let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket.key(), program_id);
+        if main_bucket.key() != &main_bucket_pda {
+            return Err(HateFunError::InvalidMainBucket.into());
+        }
+
+        let (escrow_a_pda, _) = pda::derive_escrow_a_address(bucket.key(), program_id);
+        if escrow_a.key() != &escrow_a_pda {
+            return Err(HateFunError::InvalidEscrowA.into());
+        }
+
+        let (escrow_b_pda, _) = pda::derive_escrow_b_address(bucket.key(), program_id);
+        if escrow_b.key() != &escrow_b_pda {
+            return Err(HateFunError::InvalidEscrowB.into());
+        }
+
+        let extra_escrows = verify_extra_escrows(bucket.key(), side_count, remaining, program_id)?;
+
+        Ok(Self { bucket, main_bucket, escrow_a, escrow_b, extra_escrows })
     }
+}
+
+/// Split of a collected pot, computed by VERIFIED functions
+pub(crate) struct Payout<'a> {
+    pub creator_cut: u64,
+    pub claimer_cut: u64,
+    pub winner_cut: u64,
+    /// Rent-exempt reserves of the closed PDAs, owed to the bucket's payer and not part of the pot
+    pub rent_refund: u64,
+    /// Recipients of a coalition winner and their cuts of winner_cut
+    pub coalition: Option<(&'a [Pubkey], [u64; MAX_SIDE_RECIPIENTS as usize])>,
+    /// winner_cut stays in the bucket for ClaimShare
+    pub pay_shares: bool,
+    share_contributions: u64,
+    sweep_escrows: bool,
+    keep_bucket: bool,
+}
 
-    // Get current epoch
+/// Check the claim window, collect the pot into the bucket account and split it
+/// `side_config` is only read if the winning side is a coalition
+pub(crate) fn collect_payout<'a>(
+    program_id: &Pubkey,
+    pot: &PotAccounts,
+    bucket: &mut Bucket,
+    extra_sides: &[Pubkey],
+    side_config: Option<&'a AccountInfo>,
+) -> Result<Payout<'a>, ProgramError> {
+    // Get current epoch, slot and timestamp
     let clock = Clock::get()?;
-    let current_epoch = clock.epoch;
 
-    // Verify 3 epochs have passed since last flip
-    if current_epoch < bucket.last_flip_epoch + 3 {
-        return Err(HateFunError::ClaimTooEarly.into());
+    // Verify the bucket is claimable: claim delay elapsed since last flip and not already settled
+    let status = effective_status(bucket.status()?, bucket.is_claim_window_elapsed(&clock)?);
+    next_status(status, BucketAction::Claim)?;
+
+    // Under the refund policy the escrows keep their unflushed deposits for RefundPending,
+    // so only the main bucket is paid out and the bucket stays open as Settled
+    let sweep_escrows = bucket.refund_policy()? == RefundPolicy::Sweep;
+
+    let winning_side = bucket.find_side(extra_sides, &bucket.current_target)
+        .ok_or(HateFunError::InvalidWinner)?;
+
+    // In proportional mode the winner's cut stays in the bucket for ClaimShare, unless the
+    // winning side has no recorded contributions (then the winning side is paid as usual)
+    // Snapshot before the sweep below: only deposits flushed by a flip or reinforcement earn a share
+    let share_contributions = if bucket.payout_mode()? == PayoutMode::Proportional {
+        bucket.side_flushed[winning_side as usize]
+    } else {
+        0
+    };
+
+    // Sweeping moves every escrow's pending deposits into the pot without giving them share weight:
+    // the side flush counts stay put, so the records keep those deposits as pending, not flushed.
+    // Otherwise a deposit sent together with the claim would buy a share of the losing sides' funds
+    if sweep_escrows {
+        for side in 0..bucket.side_count as usize {
+            bucket.side_pending[side] = 0;
+        }
+    }
+    let pay_shares = share_contributions > 0;
+    // A bucket with side configs also stays Settled, so CloseSettled can close the configs with it
+    let keep_bucket = !sweep_escrows || pay_shares || bucket.side_config_mask != 0;
+
+    // A coalition side splits the winner's cut among the recipients of its SideConfig
+    let coalition = if !pay_shares && bucket.has_side_config(winning_side) {
+        let side_config = side_config.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (side_config_pda, _) = pda::derive_side_config_address(pot.bucket.key(), winning_side, program_id);
+        if side_config.key() != &side_config_pda {
+            return Err(HateFunError::InvalidSideConfig.into());
+        }
+        let config = SideConfig::load(side_config, program_id)?;
+        if !config.belongs_to(pot.bucket.key(), bucket) {
+            return Err(HateFunError::StaleSideConfig.into());
+        }
+        Some(config.recipients())
+    } else {
+        None
+    };
+
+    // Split every collected PDA into prize money and its rent-exempt reserve using VERIFIED function
+    // The reserves were funded by the payer at creation, so they go back to the payer instead of the pot
+    let rent = Rent::get()?;
+    let empty_reserve = rent.minimum_balance(0);
+    let mut balances = [0u64; 2 + MAX_SIDES as usize];
+    let mut reserves = [0u64; 2 + MAX_SIDES as usize];
+    let mut sources = 0;
+    let mut collect = |account: &AccountInfo, reserve: u64| {
+        let balance = account.lamports();
+        balances[sources] = calculate_flushable_balance(balance, reserve);
+        reserves[sources] = balance - balances[sources];
+        sources += 1;
+    };
+    collect(pot.main_bucket, empty_reserve);
+    if !keep_bucket {
+        collect(pot.bucket, rent.minimum_balance(pot.bucket.data_len()));
+    }
+    if sweep_escrows {
+        for escrow in [pot.escrow_a, pot.escrow_b].into_iter().chain(pot.extra_escrows) {
+            collect(escrow, empty_reserve);
+        }
     }
 
     // Calculate total balance using VERIFIED function
-    let balances = [
-        main_bucket.lamports(),
-        escrow_a.lamports(),
-        escrow_b.lamports(),
-        bucket_account.lamports(),
-    ];
-    let total = sum_balances(&balances)
+    let total = sum_balances(&balances[..sources])
+        .ok_or(HateFunError::Overflow)?;
+    let rent_refund = sum_balances(&reserves[..sources])
+        .ok_or(HateFunError::Overflow)?;
+
+    // Lamports the bucket account keeps for itself (its rent, when it stays open)
+    let bucket_reserve = if keep_bucket { pot.bucket.lamports() } else { 0 };
+    let bucket_balance_after_collection = bucket_reserve
+        .checked_add(total)
+        .and_then(|balance| balance.checked_add(rent_refund))
         .ok_or(HateFunError::Overflow)?;
 
     // Calculate fee distributions using VERIFIED function
@@ -89,45 +267,77 @@ pub fn process_claim_payout(
         bucket.claimer_fee_bps,
     ).ok_or(HateFunError::Overflow)?;
 
-    // Transfer funds
-    // First, collect all funds to bucket account
+    // Split the winner's cut by weight using VERIFIED function
+    // Kani proved the recipients' cuts sum to winner_cut
+    let coalition = match coalition {
+        Some((recipients, weights_bps)) => Some((
+            recipients,
+            calculate_weighted_split(winner_cut, weights_bps).ok_or(HateFunError::InvalidSideWeights)?,
+        )),
+        None => None,
+    };
+
+    // Collect all funds to bucket account
     // SAFETY: These unsafe operations are justified because:
     // 1. We've verified all account ownership and PDAs above
     // 2. We've calculated total using verified sum_balances (no overflow)
     // 3. The transaction is atomic - either all transfers succeed or none do
     // 4. We collect funds first, then validate, then distribute
     unsafe {
-        *bucket_account.borrow_mut_lamports_unchecked() += balances[0] + balances[1] + balances[2];
-        *main_bucket.borrow_mut_lamports_unchecked() = 0;
-        *escrow_a.borrow_mut_lamports_unchecked() = 0;
-        *escrow_b.borrow_mut_lamports_unchecked() = 0;
+        *pot.bucket.borrow_mut_lamports_unchecked() = bucket_balance_after_collection;
+        *pot.main_bucket.borrow_mut_lamports_unchecked() = 0;
+        if sweep_escrows {
+            *pot.escrow_a.borrow_mut_lamports_unchecked() = 0;
+            *pot.escrow_b.borrow_mut_lamports_unchecked() = 0;
+            for escrow in pot.extra_escrows {
+                *escrow.borrow_mut_lamports_unchecked() = 0;
+            }
+        }
     }
 
     // Validate bucket has sufficient balance for all distributions
     // This should always pass due to value conservation proof, but serves as defense-in-depth
-    let bucket_balance_after_collection = bucket_account.lamports();
-    if bucket_balance_after_collection < total {
+    if pot.bucket.lamports() < bucket_balance_after_collection {
         return Err(HateFunError::Overflow.into()); // Insufficient funds (should never happen)
     }
 
-    // Now distribute from bucket account
-    // SAFETY: These unsafe operations are justified because:
-    // 1. We validated bucket has sufficient balance above
-    // 2. Kani proof guarantees creator_cut + claimer_cut + winner_cut = total (no underflow)
-    // 3. The transaction is atomic - either all distributions succeed or none do
-    unsafe {
-        *bucket_account.borrow_mut_lamports_unchecked() -= creator_cut;
-        *creator.borrow_mut_lamports_unchecked() += creator_cut;
+    Ok(Payout {
+        creator_cut,
+        claimer_cut,
+        winner_cut,
+        rent_refund,
+        coalition,
+        pay_shares,
+        share_contributions,
+        sweep_escrows,
+        keep_bucket,
+    })
+}
 
-        *bucket_account.borrow_mut_lamports_unchecked() -= claimer_cut;
-        *claimer.borrow_mut_lamports_unchecked() += claimer_cut;
+/// Settle the bucket once the payout left it: set the share pool aside, close every PDA
+/// that is no longer needed and mark a surviving bucket Settled
+pub(crate) fn finish_payout(pot: &PotAccounts, bucket: &mut Bucket, payout: &Payout) -> ProgramResult {
+    if payout.pay_shares {
+        bucket.share_pool = payout.winner_cut;
+        bucket.unclaimed_contributions = payout.share_contributions;
+    }
 
-        *bucket_account.borrow_mut_lamports_unchecked() -= winner_cut;
-        *winner.borrow_mut_lamports_unchecked() += winner_cut;
+    // Settlement closes every PDA it no longer needs: data wiped, resized to zero, owned by the System Program
+    close_account(pot.main_bucket)?;
+    if payout.sweep_escrows {
+        close_account(pot.escrow_a)?;
+        close_account(pot.escrow_b)?;
+        for escrow in pot.extra_escrows {
+            close_account(escrow)?;
+        }
     }
 
-    // Close all PDAs by setting their lamports to 0 and data length to 0
-    // (bucket_account lamports should now be 0 or very close to 0)
+    if payout.keep_bucket {
+        // Bucket stays alive for refunds and shares; Settled rejects everything else
+        bucket.status = BucketStatus::Settled as u8;
+        return Ok(());
+    }
 
-    Ok(())
+    // A closed bucket can never be loaded again, even later in this transaction
+    close_account(pot.bucket)
 }
@@ -0,0 +1,91 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, DepositRecord},
+    verification::{calculate_pro_rata_share, next_status, settle_deposit_record},
+};
+
+/// ClaimShare instruction data layout: none
+///
+/// Accounts: bucket, deposit record, depositor (receives the share)
+/// Permissionless: anyone can push a winning contributor's share to them once a proportional bucket is settled
+pub fn process_claim_share(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [bucket_account, deposit_record, depositor] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+
+    // Shares only exist once the claim has set the winner's cut aside
+    next_status(bucket.status()?, BucketAction::ClaimShare)?;
+    if bucket.unclaimed_contributions == 0 {
+        return Err(HateFunError::NoShareToClaim.into());
+    }
+
+    // Verify the record belongs to this bucket (not an earlier one at its address) and depositor,
+    // and to the winning side
+    let record = DepositRecord::load_mut(deposit_record, program_id)?;
+    if !record.belongs_to(bucket_account.key(), bucket) {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    if &record.depositor != depositor.key() {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    if bucket.side_address(extra_sides, record.side)? != bucket.current_target {
+        return Err(HateFunError::NotWinningSide.into());
+    }
+
+    if record.share_claimed != 0 {
+        return Err(HateFunError::ShareAlreadyClaimed.into());
+    }
+
+    // Bring the record up to date: only flips and reinforcements count, not the claim's sweep
+    let side = record.side as usize;
+    let side_flush_count = *bucket.side_flush_counts.get(side).ok_or(HateFunError::InvalidSide)?;
+    let (contribution, pending) = settle_deposit_record(
+        record.total_flushed,
+        record.pending,
+        record.flush_count,
+        side_flush_count,
+    ).ok_or(HateFunError::Overflow)?;
+    if contribution == 0 {
+        return Err(HateFunError::NoFlushedContribution.into());
+    }
+
+    // Pro-rata share of what is left using VERIFIED function
+    // Kani proved fees plus all shares add up to the pot, the last claimer taking the dust
+    let share = calculate_pro_rata_share(bucket.share_pool, contribution, bucket.unclaimed_contributions);
+
+    // SAFETY: The bucket is a program-owned PDA verified above and holds share_pool
+    // on top of its own rent; share <= share_pool
+    unsafe {
+        *bucket_account.borrow_mut_lamports_unchecked() -= share;
+        *depositor.borrow_mut_lamports_unchecked() = depositor.lamports()
+            .checked_add(share)
+            .ok_or(HateFunError::Overflow)?;
+    }
+
+    record.total_flushed = contribution;
+    record.pending = pending;
+    record.flush_count = side_flush_count;
+    record.share_claimed = 1;
+    record.share_paid = share;
+    bucket.share_pool -= share;
+    bucket.unclaimed_contributions = bucket.unclaimed_contributions.saturating_sub(contribution);
+
+    Ok(())
+}
@@ -2,34 +2,32 @@ use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvars::{rent::Rent, Sysvar},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
 use crate::{
     error::HateFunError,
-    state::{Bucket, pda},
-    verification::sum_balances,
+    state::{Bucket, BucketAction, pda},
+    verification::{can_close_bucket, next_status, sum_balances, MAX_SIDES},
 };
+use super::{close_account, close_side_configs, verify_extra_escrows, verify_side_configs};
 
 /// CloseBucket instruction has no additional data
+/// Accounts: creator, bucket, main bucket, escrow A, escrow B,
+/// then the escrows of sides 2 and up in side order, then the configs of the configured sides in side order
 pub fn process_close_bucket(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     _data: &[u8],
 ) -> ProgramResult {
     // Parse accounts
-    let [creator, bucket_account, main_bucket, escrow_a, escrow_b] = accounts else {
+    let [creator, bucket_account, main_bucket, escrow_a, escrow_b, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Verify bucket is owned by program
-    if bucket_account.owner() != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
-
-    // Load bucket state
-    let bucket = Bucket::from_account_info_unchecked(bucket_account)?;
+    // Load bucket state (verifies owner, size, discriminator and version)
+    let bucket = Bucket::load(bucket_account, program_id)?;
 
     // Verify signer is creator
     if !creator.is_signer() {
@@ -40,27 +38,50 @@ pub fn process_close_bucket(
         return Err(HateFunError::UnauthorizedClose.into());
     }
 
-    // Verify no flips have occurred (last_flip_epoch == creation_epoch)
-    if bucket.last_flip_epoch != bucket.creation_epoch {
+    // The bucket must live at the address its creator and seed derive
+    if !bucket.is_authorized_by_creator(bucket_account.key(), program_id) {
+        return Err(HateFunError::InvalidBucketAddress.into());
+    }
+
+    // Verify the bucket has not already been settled or closed
+    next_status(bucket.status()?, BucketAction::Close)?;
+
+    // Verify no flips have occurred using VERIFIED function
+    // Counters are used instead of epochs: a flip in the creation epoch leaves the epochs equal
+    if !can_close_bucket(bucket.flip_count, bucket.total_flushed) {
         return Err(HateFunError::BucketHasFlips.into());
     }
 
+    // A bucket re-created in the slot it was closed would share its creation slot with the
+    // closed one, and that slot is what tells their deposit records apart
+    if Clock::get()?.slot == bucket.creation_slot {
+        return Err(HateFunError::BucketTooNew.into());
+    }
+
     // Verify PDAs
     let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
     if main_bucket.key() != &main_bucket_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidMainBucket.into());
     }
 
     let (escrow_a_pda, _) = pda::derive_escrow_a_address(bucket_account.key(), program_id);
     if escrow_a.key() != &escrow_a_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidEscrowA.into());
     }
 
     let (escrow_b_pda, _) = pda::derive_escrow_b_address(bucket_account.key(), program_id);
     if escrow_b.key() != &escrow_b_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidEscrowB.into());
     }
 
+    let extra_escrows = verify_extra_escrows(bucket_account.key(), bucket.side_count, remaining, program_id)?;
+    let side_configs = verify_side_configs(
+        bucket_account.key(),
+        bucket.side_config_mask,
+        &remaining[extra_escrows.len()..],
+        program_id,
+    )?;
+
     // Verify escrows are empty (only contain rent-exempt balance, no user deposits)
     // FIX HF-01: Use actual rent-exempt minimum instead of arbitrary 0.01 SOL threshold
     // This prevents creators from seizing legitimate deposits below the old threshold
@@ -71,19 +92,23 @@ pub fn process_close_bucket(
     let escrow_b_balance = escrow_b.lamports();
 
     // Allow closure only if escrows contain exactly rent-exempt amount (no user deposits)
-    if escrow_a_balance > rent_exempt_minimum || escrow_b_balance > rent_exempt_minimum {
+    if escrow_a_balance > rent_exempt_minimum || escrow_b_balance > rent_exempt_minimum
+        || extra_escrows.iter().any(|escrow| escrow.lamports() > rent_exempt_minimum)
+    {
         return Err(HateFunError::EscrowsNotEmpty.into());
     }
 
     // Calculate total to return (all PDA balances including rent)
     // Use verified sum_balances function to prevent overflow
-    let balances = [
-        main_bucket.lamports(),
-        bucket_account.lamports(),
-        escrow_a_balance,
-        escrow_b_balance,
-    ];
-    let total = sum_balances(&balances).ok_or(HateFunError::Overflow)?;
+    let mut balances = [0u64; 2 + MAX_SIDES as usize];
+    balances[0] = main_bucket.lamports();
+    balances[1] = bucket_account.lamports();
+    balances[2] = escrow_a_balance;
+    balances[3] = escrow_b_balance;
+    for (balance, escrow) in balances[4..].iter_mut().zip(extra_escrows) {
+        *balance = escrow.lamports();
+    }
+    let total = sum_balances(&balances[..4 + extra_escrows.len()]).ok_or(HateFunError::Overflow)?;
 
     // Transfer all funds to creator
     // SAFETY: These unsafe operations are justified because:
@@ -96,8 +121,21 @@ pub fn process_close_bucket(
         *main_bucket.borrow_mut_lamports_unchecked() = 0;
         *escrow_a.borrow_mut_lamports_unchecked() = 0;
         *escrow_b.borrow_mut_lamports_unchecked() = 0;
+        for escrow in extra_escrows {
+            *escrow.borrow_mut_lamports_unchecked() = 0;
+        }
         *creator.borrow_mut_lamports_unchecked() += total;
     }
 
-    Ok(())
+    // Close every PDA so the bucket can never be loaded again
+    close_account(bucket_account)?;
+    close_account(main_bucket)?;
+    close_account(escrow_a)?;
+    close_account(escrow_b)?;
+    for escrow in extra_escrows {
+        close_account(escrow)?;
+    }
+
+    // The creator paid for the side configs too
+    close_side_configs(side_configs, creator, program_id)
 }
@@ -0,0 +1,96 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketStatus, DepositRecord, PayoutMode, RefundPolicy, pda},
+    verification::{can_close_deposit_record, settle_deposit_record},
+};
+use super::close_account;
+
+/// CloseDepositRecord instruction data layout: none
+///
+/// Accounts: depositor (signer, receives the record's rent), bucket, depositor's DepositRecord
+/// The depositor takes back the rent of a record that no longer counts for anything: one left by
+/// a closed or earlier bucket at this address, or one with nothing left to withdraw, refund or claim
+pub fn process_close_deposit_record(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [depositor, bucket_account, deposit_record] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Verify signer
+    if !depositor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify the record is the depositor's record for this bucket address
+    let record = DepositRecord::load_mut(deposit_record, program_id)?;
+    if &record.depositor != depositor.key() {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    let (record_pda, _) = pda::derive_deposit_record_address(
+        bucket_account.key(),
+        record.side,
+        depositor.key(),
+        program_id,
+    );
+    if deposit_record.key() != &record_pda {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    // A closed bucket leaves nothing behind to collect; neither does an earlier bucket's record
+    if bucket_account.owner() == program_id {
+        let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+        if record.belongs_to(bucket_account.key(), bucket) {
+            // Bring the record up to date before looking at what it is still owed
+            let side_flush_count = *bucket.side_flush_counts
+                .get(record.side as usize)
+                .ok_or(HateFunError::InvalidSide)?;
+            let (total_flushed, pending) = settle_deposit_record(
+                record.total_flushed,
+                record.pending,
+                record.flush_count,
+                side_flush_count,
+            ).ok_or(HateFunError::Overflow)?;
+
+            // Once a sweeping bucket is claimed, the pending deposits went into the pot
+            let settled = bucket.status()? == BucketStatus::Settled;
+            let pending_swept = settled && bucket.refund_policy()? == RefundPolicy::Sweep;
+
+            // A flushed contribution may still earn a share: any time before the claim,
+            // and afterwards if it backed the winning side and was not claimed yet
+            let share_owed = bucket.payout_mode()? == PayoutMode::Proportional
+                && total_flushed > 0
+                && record.share_claimed == 0
+                && (!settled || (bucket.unclaimed_contributions > 0
+                    && bucket.side_address(extra_sides, record.side)? == bucket.current_target));
+
+            // Use VERIFIED function
+            if !can_close_deposit_record(pending, pending_swept, share_owed) {
+                return Err(HateFunError::DepositRecordInUse.into());
+            }
+
+            bucket.deposit_record_count = bucket.deposit_record_count.saturating_sub(1);
+        }
+    }
+
+    // SAFETY: The record is a program-owned PDA verified above and a different account than the depositor
+    unsafe {
+        *depositor.borrow_mut_lamports_unchecked() = depositor.lamports()
+            .checked_add(deposit_record.lamports())
+            .ok_or(HateFunError::Overflow)?;
+        *deposit_record.borrow_mut_lamports_unchecked() = 0;
+    }
+
+    close_account(deposit_record)
+}
@@ -0,0 +1,113 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, pda},
+    verification::{is_settlement_drained, next_status, sum_balances, MAX_SIDES},
+};
+use super::{close_account, close_side_configs, verify_extra_escrows, verify_side_configs};
+
+/// CloseSettled instruction has no additional data
+/// Accounts: bucket, payer, escrow A, escrow B,
+/// then the escrows of sides 2 and up in side order,
+/// then, if any side has a config, the creator followed by the configs of the configured sides in side order
+/// Permissionless: once every refund and share is paid out, anyone can hand the rent back to the payer
+/// (and the side configs' rent back to the creator, who paid for them)
+pub fn process_close_settled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [bucket_account, payer, escrow_a, escrow_b, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state (verifies owner, size, discriminator and version)
+    let bucket = Bucket::load(bucket_account, program_id)?;
+
+    // Only a settled bucket can be closed this way
+    next_status(bucket.status()?, BucketAction::CloseSettled)?;
+
+    // The rent goes back to whoever funded the accounts
+    if payer.key() != &bucket.payer {
+        return Err(HateFunError::InvalidPayer.into());
+    }
+
+    // Verify nobody is still owed a refund or a share using VERIFIED function
+    let side_pending = bucket.side_pending.get(..bucket.side_count as usize)
+        .ok_or(HateFunError::InvalidSide)?;
+    if !is_settlement_drained(side_pending, bucket.share_pool, bucket.unclaimed_contributions) {
+        return Err(HateFunError::SettlementNotDrained.into());
+    }
+
+    // Verify PDAs
+    let (escrow_a_pda, _) = pda::derive_escrow_a_address(bucket_account.key(), program_id);
+    if escrow_a.key() != &escrow_a_pda {
+        return Err(HateFunError::InvalidEscrowA.into());
+    }
+
+    let (escrow_b_pda, _) = pda::derive_escrow_b_address(bucket_account.key(), program_id);
+    if escrow_b.key() != &escrow_b_pda {
+        return Err(HateFunError::InvalidEscrowB.into());
+    }
+
+    let extra_escrows = verify_extra_escrows(bucket_account.key(), bucket.side_count, remaining, program_id)?;
+    let side_configs = match &remaining[extra_escrows.len()..] {
+        _ if bucket.side_config_mask == 0 => None,
+        [creator, side_configs @ ..] => {
+            if creator.key() != &bucket.creator_address {
+                return Err(HateFunError::InvalidCreator.into());
+            }
+            let side_configs = verify_side_configs(bucket_account.key(), bucket.side_config_mask, side_configs, program_id)?;
+            Some((creator, side_configs))
+        }
+        [] => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    // Escrows swept by the claim are already closed; only the ones still owned by the program are left
+    let mut escrows = [escrow_a; MAX_SIDES as usize];
+    escrows[1] = escrow_b;
+    for (slot, escrow) in escrows[2..].iter_mut().zip(extra_escrows) {
+        *slot = escrow;
+    }
+    let escrows = &escrows[..2 + extra_escrows.len()];
+
+    // Calculate total to return using verified sum_balances to prevent overflow
+    let mut balances = [0u64; 1 + MAX_SIDES as usize];
+    balances[0] = bucket_account.lamports();
+    for (balance, escrow) in balances[1..].iter_mut().zip(escrows) {
+        if escrow.owner() == program_id {
+            *balance = escrow.lamports();
+        }
+    }
+    let total = sum_balances(&balances[..1 + escrows.len()]).ok_or(HateFunError::Overflow)?;
+
+    // SAFETY: Every account drained here is a program-owned PDA verified above,
+    // and each source is zeroed before the payer is credited
+    unsafe {
+        *bucket_account.borrow_mut_lamports_unchecked() = 0;
+        for escrow in escrows.iter().filter(|escrow| escrow.owner() == program_id) {
+            *escrow.borrow_mut_lamports_unchecked() = 0;
+        }
+        *payer.borrow_mut_lamports_unchecked() = payer.lamports()
+            .checked_add(total)
+            .ok_or(HateFunError::Overflow)?;
+    }
+
+    // Close every remaining PDA so the bucket can never be loaded again
+    close_account(bucket_account)?;
+    for escrow in escrows.iter().filter(|escrow| escrow.owner() == program_id) {
+        close_account(escrow)?;
+    }
+
+    match side_configs {
+        Some((creator, side_configs)) => close_side_configs(side_configs, creator, program_id),
+        None => Ok(()),
+    }
+}
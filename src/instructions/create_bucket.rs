@@ -2,16 +2,25 @@ use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    sysvars::{
+        clock::Clock,
+        rent::Rent,
+        slot_hashes::{fetch_into, ENTRY_SIZE, NUM_ENTRIES_SIZE},
+        Sysvar,
+    },
     ProgramResult,
 };
 use pinocchio::instruction::Seed;
 
 use crate::{
     error::HateFunError,
-    state::{Bucket, pda},
+    state::{Bucket, BucketStatus, ClaimDelayUnit, PayoutMode, RefundPolicy, StartSource, pda},
     system_program,
-    verification::{validate_fees, validate_min_increase},
+    verification::{
+        has_unique_addresses, meets_min_initial_swap, select_random_side, validate_claim_delay,
+        validate_fees, validate_min_deposit, validate_min_increase, validate_min_initial_swap,
+        validate_side_count, MAX_SIDES,
+    },
 };
 use super::{read_u64, read_u16, read_pubkey};
 
@@ -24,13 +33,31 @@ use super::{read_u64, read_u16, read_pubkey};
 /// [100..108] initial_last_swap: u64
 /// [108..110] min_increase_bps: u16
 /// [110..142] seed: [u8; 32]
+/// [142]     claim_delay_unit: u8 (0 = epochs, 1 = slots, 2 = seconds)
+/// [143..151] claim_delay: u64
+/// [151..159] min_deposit: u64
+/// [159..167] min_initial_swap: u64
+/// [167]     side_count: u8 (optional, 2-16; absent = 2)
+/// [168..]   addresses of sides 2 and up: [Pubkey; side_count - 2]
+/// [next]    refund_policy: u8 (optional, 0 = sweep, 1 = refund; absent = sweep)
+/// [next]    withdrawals_disabled: u8 (optional, 0 = depositors may withdraw pending deposits, 1 = disabled; absent = 0)
+/// [next]    payout_mode: u8 (optional, 0 = winner address, 1 = proportional to winning contributors; absent = winner)
+/// [next]    starting_side: u8 (optional, side index in control at creation, 255 = random from SlotHashes; absent = side A)
+///
+/// Accounts: payer (signer, funds the rent), creator (signer, must be creator_address; may be the payer),
+/// bucket, main bucket, escrow A, escrow B, System Program,
+/// then the escrows of sides 2 and up in side order
+///
+/// Each side adds 65 bytes to the transaction (address, escrow key and index), so a legacy
+/// transaction fits at most 13 sides; larger buckets need a v0 transaction that loads the
+/// extra escrows from an address lookup table
 pub fn process_create_bucket(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
     // Parse instruction data
-    if data.len() < 142 {
+    if data.len() < 167 {
         return Err(ProgramError::InvalidInstructionData);
     }
 
@@ -45,34 +72,101 @@ pub fn process_create_bucket(
     let mut seed_bytes = [0u8; 32];
     seed_bytes.copy_from_slice(&data[110..142]);
 
+    let claim_delay_unit = ClaimDelayUnit::try_from(data[142])?;
+    let claim_delay = read_u64(data, 143)?;
+    let min_deposit = read_u64(data, 151)?;
+    let min_initial_swap = read_u64(data, 159)?;
+
+    // Optional trailing side list; every side's address goes in `sides`
+    let side_count = data.get(167).copied().unwrap_or(2);
+    if !validate_side_count(side_count) {
+        return Err(HateFunError::InvalidSideCount.into());
+    }
+
+    let mut sides = [Pubkey::default(); MAX_SIDES as usize];
+    sides[0] = address_a;
+    sides[1] = address_b;
+    for (index, address) in sides[2..side_count as usize].iter_mut().enumerate() {
+        *address = read_pubkey(data, 168 + 32 * index)?;
+    }
+    let sides = &sides[..side_count as usize];
+
+    let refund_policy_offset = 168 + 32 * (side_count as usize - 2);
+    let refund_policy = match data.get(refund_policy_offset) {
+        Some(&policy) => RefundPolicy::try_from(policy)?,
+        None => RefundPolicy::Sweep,
+    };
+
+    let withdrawals_disabled = match data.get(refund_policy_offset + 1) {
+        Some(&flag @ (0 | 1)) => flag,
+        Some(_) => return Err(ProgramError::InvalidInstructionData),
+        None => 0,
+    };
+
+    let payout_mode = match data.get(refund_policy_offset + 2) {
+        Some(&mode) => PayoutMode::try_from(mode)?,
+        None => PayoutMode::Winner,
+    };
+
+    let starting_side = data.get(refund_policy_offset + 3).copied().unwrap_or(0);
+    if starting_side >= side_count && starting_side != Bucket::STARTING_SIDE_RANDOM {
+        return Err(HateFunError::InvalidStartingSide.into());
+    }
+
     // Validate parameters using VERIFIED functions
     // Kani proved these enforce the correct bounds
     if !validate_fees(creator_fee_bps, claimer_fee_bps) {
         return Err(HateFunError::FeesTooHigh.into());
     }
 
-    if creator_address == address_a || creator_address == address_b {
+    if sides.contains(&creator_address) {
         return Err(HateFunError::CreatorMustBeDifferent.into());
     }
 
+    if !has_unique_addresses(sides) {
+        return Err(HateFunError::DuplicateSideAddress.into());
+    }
+
     if !validate_min_increase(min_increase_bps) {
         return Err(HateFunError::InvalidMinimumIncrease.into());
     }
 
-    if initial_last_swap < 100_000 {
+    if !validate_min_initial_swap(min_initial_swap) {
+        return Err(HateFunError::InvalidMinimumInitialSwap.into());
+    }
+
+    if !meets_min_initial_swap(initial_last_swap, min_initial_swap) {
         return Err(HateFunError::InitialSwapTooLow.into());
     }
 
+    if !validate_min_deposit(min_deposit) {
+        return Err(HateFunError::InvalidMinimumDeposit.into());
+    }
+
+    if !validate_claim_delay(claim_delay_unit, claim_delay) {
+        return Err(HateFunError::InvalidClaimDelay.into());
+    }
+
     // Parse accounts
-    let [payer, bucket_account, main_bucket, escrow_a, escrow_b, _system_program] = accounts else {
+    let [payer, creator, bucket_account, main_bucket, escrow_a, escrow_b, _system_program, extra_escrows @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    let extra_escrows = extra_escrows
+        .get(..side_count as usize - 2)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
     // Verify signer
     if !payer.is_signer() {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    // The bucket PDA lives in the creator's namespace, so only the creator may create it;
+    // the rent may come from someone else, who gets the reserves back at settlement
+    if creator.key() != &creator_address || !creator.is_signer() {
+        return Err(HateFunError::CreatorMustSign.into());
+    }
+
     // Derive PDAs
     let (bucket_pda, bucket_bump) = pda::derive_bucket_address(
         &creator_address,
@@ -81,33 +175,54 @@ pub fn process_create_bucket(
     );
 
     if bucket_account.key() != &bucket_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidBucketAddress.into());
     }
 
     let (main_bucket_pda, main_bump) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
     if main_bucket.key() != &main_bucket_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidMainBucket.into());
     }
 
     let (escrow_a_pda, escrow_a_bump) = pda::derive_escrow_a_address(bucket_account.key(), program_id);
     if escrow_a.key() != &escrow_a_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidEscrowA.into());
     }
 
     let (escrow_b_pda, escrow_b_bump) = pda::derive_escrow_b_address(bucket_account.key(), program_id);
     if escrow_b.key() != &escrow_b_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidEscrowB.into());
     }
 
     // Get current epoch
     let clock = Clock::get()?;
     let current_epoch = clock.epoch;
+    let current_slot = clock.slot;
+
+    // Random start: draw the side from the most recent SlotHashes entry using VERIFIED function
+    // Kani proved the drawn side is always in range. Only the creator can submit CreateBucket and
+    // this entry is known before submission, so the creator can simulate and resubmit until the
+    // side they want comes up: a random start is no fairer than a chosen one, only recorded differently
+    let (starting_side, start_source, start_slot, start_slot_hash) = if starting_side == Bucket::STARTING_SIDE_RANDOM {
+        let mut slot_hashes = [0u8; NUM_ENTRIES_SIZE + ENTRY_SIZE];
+        if fetch_into(&mut slot_hashes, 0)? == 0 {
+            return Err(ProgramError::UnsupportedSysvar);
+        }
+        let start_slot = read_u64(&slot_hashes, NUM_ENTRIES_SIZE)?;
+        let mut start_slot_hash = [0u8; 32];
+        start_slot_hash.copy_from_slice(&slot_hashes[NUM_ENTRIES_SIZE + 8..]);
+        let side = select_random_side(&start_slot_hash, side_count).ok_or(HateFunError::InvalidSideCount)?;
+        (side, StartSource::SlotHashes, start_slot, start_slot_hash)
+    } else {
+        (starting_side, StartSource::Creator, 0, [0u8; 32])
+    };
 
     // Calculate rent exemption
     let rent = Rent::get()?;
-    let bucket_rent = rent.minimum_balance(Bucket::SIZE);
+    let bucket_space = Bucket::space(side_count);
+    let bucket_rent = rent.minimum_balance(bucket_space);
     let escrow_rent = rent.minimum_balance(0); // Empty accounts
 
+    // Every address below is predictable, so initialize_pda tolerates lamports sent ahead of time
     // Create bucket account
     let bucket_bump_arr = [bucket_bump];
     let bucket_seeds = [
@@ -117,11 +232,11 @@ pub fn process_create_bucket(
         Seed::from(&bucket_bump_arr),
     ];
 
-    system_program::create_account(
+    system_program::initialize_pda(
         payer,
         bucket_account,
         bucket_rent,
-        Bucket::SIZE as u64,
+        bucket_space as u64,
         program_id,
         &bucket_seeds,
     )?;
@@ -134,7 +249,7 @@ pub fn process_create_bucket(
         Seed::from(&main_bump_arr),
     ];
 
-    system_program::create_account(
+    system_program::initialize_pda(
         payer,
         main_bucket,
         escrow_rent,
@@ -151,7 +266,7 @@ pub fn process_create_bucket(
         Seed::from(&escrow_a_bump_arr),
     ];
 
-    system_program::create_account(
+    system_program::initialize_pda(
         payer,
         escrow_a,
         escrow_rent,
@@ -168,7 +283,7 @@ pub fn process_create_bucket(
         Seed::from(&escrow_b_bump_arr),
     ];
 
-    system_program::create_account(
+    system_program::initialize_pda(
         payer,
         escrow_b,
         escrow_rent,
@@ -177,19 +292,76 @@ pub fn process_create_bucket(
         &escrow_b_seeds,
     )?;
 
-    // Initialize bucket state
-    let bucket = Bucket::from_account_info(bucket_account)?;
+    // Create the escrow PDAs of sides 2 and up
+    for (index, escrow) in extra_escrows.iter().enumerate() {
+        let side = index as u8 + 2;
+        let (escrow_pda, escrow_bump) = pda::derive_escrow_address(bucket_account.key(), side, program_id)?;
+        if escrow.key() != &escrow_pda {
+            return Err(HateFunError::InvalidEscrow.into());
+        }
+
+        let side_arr = [side];
+        let escrow_bump_arr = [escrow_bump];
+        let escrow_seeds = [
+            Seed::from(Bucket::ESCROW_SEED_PREFIX),
+            Seed::from(bucket_account.key().as_ref()),
+            Seed::from(&side_arr),
+            Seed::from(&escrow_bump_arr),
+        ];
+
+        system_program::initialize_pda(
+            payer,
+            escrow,
+            escrow_rent,
+            0,
+            program_id,
+            &escrow_seeds,
+        )?;
+    }
+
+    // Initialize bucket state (stamps discriminator, layout version and side count)
+    let bucket = Bucket::init(bucket_account, program_id, side_count)?;
     bucket.address_a = address_a;
     bucket.address_b = address_b;
     bucket.creator_address = creator_address;
-    bucket.current_target = address_a; // Start pointing at address A
+    bucket.seed = seed_bytes;
+    bucket.payer = *payer.key();
+    bucket.current_target = sides[starting_side as usize]; // Start pointing at the starting side
+    bucket.starting_side = starting_side;
+    bucket.start_source = start_source as u8;
+    bucket.start_slot = start_slot;
+    bucket.start_slot_hash = start_slot_hash;
     bucket.last_swap = initial_last_swap;
     bucket.creation_epoch = current_epoch;
+    bucket.creation_slot = current_slot;
     bucket.last_flip_epoch = current_epoch;
+    bucket.last_flip_slot = current_slot;
+    bucket.last_flip_timestamp = clock.unix_timestamp;
+    bucket.flip_count = 0;
+    bucket.total_flushed = 0;
+    bucket.reinforce_count = 0;
+    bucket.side_flush_counts = [0; MAX_SIDES as usize];
+    bucket.side_pending = [0; MAX_SIDES as usize];
+    bucket.side_flushed = [0; MAX_SIDES as usize];
+    bucket.share_pool = 0;
+    bucket.unclaimed_contributions = 0;
+    bucket.side_config_mask = 0;
+    bucket.deposit_record_count = 0;
+    bucket.refund_policy = refund_policy as u8;
+    bucket.withdrawals_disabled = withdrawals_disabled;
+    bucket.payout_mode = payout_mode as u8;
     bucket.creator_fee_bps = creator_fee_bps;
     bucket.claimer_fee_bps = claimer_fee_bps;
     bucket.min_increase_bps = min_increase_bps;
     bucket.bump = bucket_bump;
+    bucket.status = BucketStatus::Open as u8;
+    bucket.claim_delay_unit = claim_delay_unit as u8;
+    bucket.claim_delay = claim_delay;
+    bucket.min_deposit = min_deposit;
+    bucket.min_initial_swap = min_initial_swap;
+
+    // Sides 2 and up follow the header
+    Bucket::load_sides_mut(bucket_account, program_id)?.copy_from_slice(&sides[2..]);
 
     Ok(())
 }
@@ -0,0 +1,152 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio::instruction::Seed;
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, SideConfig, pda},
+    system_program,
+    verification::{
+        can_close_bucket, has_unique_addresses, next_status, validate_side_weights,
+        MAX_SIDE_RECIPIENTS,
+    },
+};
+use super::{read_pubkey, read_u16};
+
+/// CreateSideConfig instruction data layout:
+/// [0]    side: u8
+/// [1]    recipient_count: u8 (1-8)
+/// [2..]  recipient_count × (recipient: Pubkey, weight_bps: u16), weights summing to 10000
+///
+/// Accounts: creator (signer, pays rent), bucket, side's SideConfig, System Program
+/// Only accepted in the bucket's creation slot: send it in the same transaction as CreateBucket
+pub fn process_create_side_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data
+    if data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let side = data[0];
+    let recipient_count = data[1] as usize;
+    if recipient_count == 0 || recipient_count > MAX_SIDE_RECIPIENTS as usize {
+        return Err(HateFunError::InvalidSideWeights.into());
+    }
+
+    let mut recipients = [Pubkey::default(); MAX_SIDE_RECIPIENTS as usize];
+    let mut weights_bps = [0u16; MAX_SIDE_RECIPIENTS as usize];
+    for index in 0..recipient_count {
+        let offset = 2 + 34 * index;
+        recipients[index] = read_pubkey(data, offset)?;
+        weights_bps[index] = read_u16(data, offset + 32)?;
+    }
+    let recipients = &recipients[..recipient_count];
+    let weights_bps = &weights_bps[..recipient_count];
+
+    // Validate the split using VERIFIED functions
+    if !validate_side_weights(weights_bps) {
+        return Err(HateFunError::InvalidSideWeights.into());
+    }
+
+    if !has_unique_addresses(recipients) {
+        return Err(HateFunError::DuplicateRecipient.into());
+    }
+
+    // Parse accounts
+    let [creator, bucket_account, side_config, _system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state (verifies owner, size, discriminator and version)
+    let bucket = Bucket::load_mut(bucket_account, program_id)?;
+    bucket.check_side(side)?;
+
+    // Verify signer is creator
+    if !creator.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if creator.key() != &bucket.creator_address {
+        return Err(HateFunError::InvalidCreator.into());
+    }
+
+    // The bucket must live at the address its creator and seed derive
+    if !bucket.is_authorized_by_creator(bucket_account.key(), program_id) {
+        return Err(HateFunError::InvalidBucketAddress.into());
+    }
+
+    // Coalitions are part of the bucket's terms: they can only be set in the slot the bucket
+    // was created (send them in the CreateBucket transaction, where no deposit can get in
+    // between) and before anyone deposited, so no depositor ever backs a side whose winnings
+    // were redirected after the fact
+    next_status(bucket.status()?, BucketAction::ConfigureSides)?;
+    if Clock::get()?.slot != bucket.creation_slot
+        || !can_close_bucket(bucket.flip_count, bucket.total_flushed)
+        || bucket.deposit_record_count > 0
+        || bucket.side_pending.iter().any(|&pending| pending > 0)
+    {
+        return Err(HateFunError::SideConfigLocked.into());
+    }
+
+    // A side gets at most one config per bucket
+    if bucket.has_side_config(side) {
+        return Err(HateFunError::SideAlreadyConfigured.into());
+    }
+
+    let (side_config_pda, side_config_bump) = pda::derive_side_config_address(bucket_account.key(), side, program_id);
+    if side_config.key() != &side_config_pda {
+        return Err(HateFunError::InvalidSideConfig.into());
+    }
+
+    // Create the config PDA, or take over the one an earlier bucket at this address left behind
+    let config = if side_config.owner() == program_id {
+        let config = SideConfig::load_mut(side_config, program_id)?;
+        if config.belongs_to(bucket_account.key(), bucket) {
+            return Err(HateFunError::SideAlreadyConfigured.into());
+        }
+        config.recipients = [Pubkey::default(); MAX_SIDE_RECIPIENTS as usize];
+        config.weights_bps = [0; MAX_SIDE_RECIPIENTS as usize];
+        config
+    } else {
+        let rent = Rent::get()?;
+        let side_arr = [side];
+        let side_config_bump_arr = [side_config_bump];
+        let side_config_seeds = [
+            Seed::from(SideConfig::SEED_PREFIX),
+            Seed::from(bucket_account.key().as_ref()),
+            Seed::from(&side_arr),
+            Seed::from(&side_config_bump_arr),
+        ];
+
+        system_program::initialize_pda(
+            creator,
+            side_config,
+            rent.minimum_balance(SideConfig::SIZE),
+            SideConfig::SIZE as u64,
+            program_id,
+            &side_config_seeds,
+        )?;
+
+        SideConfig::init(side_config, program_id)?
+    };
+
+    config.bump = side_config_bump;
+    config.side = side;
+    config.recipient_count = recipient_count as u8;
+    config.bucket = *bucket_account.key();
+    config.bucket_creation_slot = bucket.creation_slot;
+    config.recipients[..recipient_count].copy_from_slice(recipients);
+    config.weights_bps[..recipient_count].copy_from_slice(weights_bps);
+
+    bucket.side_config_mask |= 1 << side;
+
+    Ok(())
+}
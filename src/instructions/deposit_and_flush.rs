@@ -0,0 +1,114 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, pda},
+    system_program,
+    verification::{
+        calculate_flip_deposit, calculate_flush_threshold, calculate_flushable_balance,
+        effective_status, meets_min_deposit, next_status,
+    },
+};
+use super::deposit_to_escrow::record_deposit_receipt;
+use super::flush_escrow::flush_to_main;
+use super::{read_swap_guard, read_u64};
+
+/// DepositAndFlush instruction data layout:
+/// [0..8] amount: u64 (0 = deposit exactly what the flip needs)
+/// [8]    side: u8 (0 = A, 1 = B, 2.. = further sides)
+/// [9..58] optional swap guard (see read_swap_guard); max_lamports caps the deposit
+///
+/// Accounts: depositor (signer), bucket, main bucket, side's escrow, depositor's DepositRecord,
+/// System Program
+pub fn process_deposit_and_flush(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data
+    if data.len() < 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let requested_amount = read_u64(data, 0)?;
+    let side = data[8];
+    let guard = read_swap_guard(data, 9)?;
+
+    // Parse accounts
+    let [depositor, bucket_account, main_bucket, target_escrow, deposit_record, _system_program_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Verify signer
+    if !depositor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+    bucket.check_side(side)?;
+
+    // Get current epoch, slot and timestamp
+    let clock = Clock::get()?;
+
+    // Same gate as FlushEscrow: allowed until settled, and a flip restarts the claim window
+    let status = effective_status(bucket.status()?, bucket.is_claim_window_elapsed(&clock)?);
+    let new_status = next_status(status, BucketAction::Flush)?;
+
+    // Verify escrow is the chosen side's PDA for this bucket and still alive
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), side, program_id)?;
+    if target_escrow.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(side).into());
+    }
+
+    if target_escrow.owner() != program_id {
+        return Err(HateFunError::EscrowNotInitialized.into());
+    }
+
+    // Verify main bucket PDA
+    let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
+    if main_bucket.key() != &main_bucket_pda {
+        return Err(HateFunError::InvalidMainBucket.into());
+    }
+
+    // Reject a flip that would hand nothing over before moving any funds
+    let flushed_side = bucket.side_address(extra_sides, side)?;
+    if flushed_side == bucket.current_target {
+        return Err(HateFunError::SideAlreadyInControl.into());
+    }
+
+    // Work out the deposit: the caller's amount, or just enough to reach the threshold
+    let amount = if requested_amount == 0 {
+        let rent = Rent::get()?;
+        let flushable = calculate_flushable_balance(target_escrow.lamports(), rent.minimum_balance(0));
+        let threshold = calculate_flush_threshold(bucket.last_swap, bucket.min_increase_bps)
+            .ok_or(HateFunError::Overflow)?;
+        calculate_flip_deposit(flushable, threshold, bucket.min_deposit)
+    } else {
+        if !meets_min_deposit(requested_amount, bucket.min_deposit) {
+            return Err(HateFunError::DepositTooSmall.into());
+        }
+        requested_amount
+    };
+
+    // Abort if the bucket moved since the depositor built the transaction,
+    // or if the computed deposit grew past what the depositor is willing to pay
+    guard.check(bucket.last_swap, &bucket.current_target, amount)?;
+
+    // Transfer lamports from depositor to escrow (the bucket account is not part of this CPI)
+    // The receipt is written before the flip, so the deposit counts as flushed by it
+    if amount > 0 {
+        system_program::transfer(depositor, target_escrow, amount)?;
+        record_deposit_receipt(program_id, bucket_account, bucket, side, depositor, deposit_record, amount)?;
+    }
+
+    // Flip in the same instruction; an unmet threshold fails the whole transaction,
+    // which also reverts the deposit above
+    flush_to_main(bucket, main_bucket, target_escrow, side, &flushed_side, &clock, new_status)
+}
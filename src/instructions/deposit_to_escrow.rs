@@ -1,26 +1,39 @@
 use pinocchio::{
     account_info::AccountInfo,
+    instruction::Seed,
     program_error::ProgramError,
     pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
-use crate::{error::HateFunError, system_program};
-use super::read_u64;
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, DepositRecord, pda},
+    system_program,
+    verification::{meets_min_deposit, next_status, record_deposit, settle_deposit_record},
+};
+use super::{read_swap_guard, read_u64};
 
 /// DepositToEscrow instruction data layout:
 /// [0..8] amount: u64
+/// [8]    side: u8 (0 = A, 1 = B, 2.. = further sides)
+/// [9..58] optional swap guard (see read_swap_guard)
+///
+/// Accounts: depositor (signer), bucket, side's escrow, depositor's DepositRecord, System Program
 pub fn process_deposit_to_escrow(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
     // Parse instruction data
-    if data.len() < 8 {
+    if data.len() < 9 {
         return Err(ProgramError::InvalidInstructionData);
     }
 
     let amount = read_u64(data, 0)?;
+    let side = data[8];
+    let guard = read_swap_guard(data, 9)?;
 
     // Validate deposit amount
     // Prevent zero deposits (standardized to use HateFunError)
@@ -28,15 +41,8 @@ pub fn process_deposit_to_escrow(
         return Err(HateFunError::ZeroAmountDeposit.into());
     }
 
-    // Prevent dust deposits that could complicate bucket closure
-    // Minimum 0.000001 SOL (1,000 lamports) to prevent griefing
-    const MINIMUM_DEPOSIT: u64 = 1_000; // 0.000001 SOL
-    if amount < MINIMUM_DEPOSIT {
-        return Err(HateFunError::DepositTooSmall.into());
-    }
-
     // Parse accounts
-    let [depositor, target_escrow, _system_program_account] = accounts else {
+    let [depositor, bucket_account, target_escrow, deposit_record, _system_program_account] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -45,13 +51,119 @@ pub fn process_deposit_to_escrow(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Verify escrow is owned by program
+    // Reject deposits into settled or closed buckets
+    let bucket = Bucket::load_mut(bucket_account, program_id)?;
+    next_status(bucket.status()?, BucketAction::Deposit)?;
+    bucket.check_side(side)?;
+
+    // Abort if the bucket moved since the depositor built the transaction
+    guard.check(bucket.last_swap, &bucket.current_target, amount)?;
+
+    // Prevent dust deposits that could complicate bucket closure
+    // The bucket's minimum is never below 0.000001 SOL (1,000 lamports), see validate_min_deposit
+    if !meets_min_deposit(amount, bucket.min_deposit) {
+        return Err(HateFunError::DepositTooSmall.into());
+    }
+
+    // Verify escrow is the chosen side's PDA for this bucket
+    // Rejects the bucket account, main bucket and other buckets' escrows
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), side, program_id)?;
+    if target_escrow.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(side).into());
+    }
+
+    // Verify escrow is owned by program (still alive)
     if target_escrow.owner() != program_id {
-        return Err(HateFunError::InvalidEscrow.into());
+        return Err(HateFunError::EscrowNotInitialized.into());
     }
 
     // Transfer lamports from depositor to escrow
     system_program::transfer(depositor, target_escrow, amount)?;
 
+    // Keep a receipt of who deposited how much to which side
+    record_deposit_receipt(program_id, bucket_account, bucket, side, depositor, deposit_record, amount)
+}
+
+/// Create the depositor's DepositRecord for this side on first use (or reset one left by an
+/// earlier bucket at the same address), then add `amount` to it
+/// and to the side's pending total (the denominator of pro-rata refunds)
+/// Must run before any flush in the same instruction so the deposit is counted as pending first
+pub(crate) fn record_deposit_receipt(
+    program_id: &Pubkey,
+    bucket_account: &AccountInfo,
+    bucket: &mut Bucket,
+    side: u8,
+    depositor: &AccountInfo,
+    deposit_record: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let (record_pda, record_bump) = pda::derive_deposit_record_address(
+        bucket_account.key(),
+        side,
+        depositor.key(),
+        program_id,
+    );
+    if deposit_record.key() != &record_pda {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    let side_flush_count = *bucket.side_flush_counts
+        .get(side as usize)
+        .ok_or(HateFunError::InvalidSide)?;
+
+    // First deposit to this side: the depositor pays for their own record
+    if deposit_record.owner() != program_id {
+        let rent = Rent::get()?;
+        let side_arr = [side];
+        let record_bump_arr = [record_bump];
+        let record_seeds = [
+            Seed::from(DepositRecord::SEED_PREFIX),
+            Seed::from(bucket_account.key().as_ref()),
+            Seed::from(&side_arr),
+            Seed::from(depositor.key().as_ref()),
+            Seed::from(&record_bump_arr),
+        ];
+
+        system_program::initialize_pda(
+            depositor,
+            deposit_record,
+            rent.minimum_balance(DepositRecord::SIZE),
+            DepositRecord::SIZE as u64,
+            program_id,
+            &record_seeds,
+        )?;
+
+        let record = DepositRecord::init(deposit_record, program_id)?;
+        record.bump = record_bump;
+        record.side = side;
+        record.depositor = *depositor.key();
+    }
+
+    // A new record, or one left over from an earlier bucket at this address, starts from zero
+    let record = DepositRecord::load_mut(deposit_record, program_id)?;
+    if !record.belongs_to(bucket_account.key(), bucket) {
+        record.reset(bucket_account.key(), bucket, side_flush_count);
+        bucket.deposit_record_count = bucket.deposit_record_count.saturating_add(1);
+    }
+
+    // Settle against the side's flushes since the last deposit, then add this one (VERIFIED functions)
+    let (total_flushed, pending) = settle_deposit_record(
+        record.total_flushed,
+        record.pending,
+        record.flush_count,
+        side_flush_count,
+    ).ok_or(HateFunError::Overflow)?;
+    let (total_contributed, pending) = record_deposit(record.total_contributed, pending, amount)
+        .ok_or(HateFunError::Overflow)?;
+
+    record.total_contributed = total_contributed;
+    record.total_flushed = total_flushed;
+    record.pending = pending;
+    record.flush_count = side_flush_count;
+    record.last_deposit_slot = Clock::get()?.slot;
+
+    let side_pending = &mut bucket.side_pending[side as usize];
+    *side_pending = side_pending.checked_add(amount).ok_or(HateFunError::Overflow)?;
+
     Ok(())
 }
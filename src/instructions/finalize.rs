@@ -0,0 +1,103 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio::instruction::Seed;
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, Settlement, pda},
+    system_program,
+};
+use super::claim_payout::{collect_payout, finish_payout, PotAccounts};
+
+/// Finalize instruction has no additional data
+/// Accounts: claimer (signer, pays the settlement's rent and earns the claimer fee), bucket,
+/// main bucket, escrow A, escrow B, settlement, System Program,
+/// then the escrows of sides 2 and up in side order,
+/// then the winning side's SideConfig if it has one
+///
+/// Same checks and split as ClaimPayout, but the cuts are moved into a Settlement PDA
+/// instead of being paid out; each entry is then paid out separately with Withdraw
+pub fn process_finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [claimer, bucket_account, main_bucket, escrow_a, escrow_b, settlement_account, _system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+
+    // Verify signer is claimer
+    if !claimer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify PDAs
+    let pot = PotAccounts::verify(program_id, bucket_account, main_bucket, escrow_a, escrow_b, bucket.side_count, remaining)?;
+    let side_config = remaining.get(pot.extra_escrows.len());
+
+    let (settlement_pda, settlement_bump) = pda::derive_settlement_address(bucket_account.key(), bucket.creation_slot, program_id);
+    if settlement_account.key() != &settlement_pda {
+        return Err(HateFunError::InvalidSettlement.into());
+    }
+
+    // Create the settlement before any lamports move; a second finalize fails here
+    let rent = Rent::get()?;
+    let creation_slot_bytes = bucket.creation_slot.to_le_bytes();
+    let settlement_bump_arr = [settlement_bump];
+    let settlement_seeds = [
+        Seed::from(Settlement::SEED_PREFIX),
+        Seed::from(bucket_account.key().as_ref()),
+        Seed::from(&creation_slot_bytes),
+        Seed::from(&settlement_bump_arr),
+    ];
+
+    system_program::initialize_pda(
+        claimer,
+        settlement_account,
+        rent.minimum_balance(Settlement::SIZE),
+        Settlement::SIZE as u64,
+        program_id,
+        &settlement_seeds,
+    )?;
+
+    let payout = collect_payout(program_id, &pot, bucket, extra_sides, side_config)?;
+
+    // Freeze the split: creator, claimer, the payer's rent refund, then the winner or the coalition's recipients
+    let settlement = Settlement::init(settlement_account, program_id)?;
+    settlement.bump = settlement_bump;
+    settlement.bucket = *bucket_account.key();
+    settlement.push(&bucket.creator_address, payout.creator_cut)?;
+    settlement.push(claimer.key(), payout.claimer_cut)?;
+    settlement.push(&bucket.payer, payout.rent_refund)?;
+    if let Some((recipients, recipient_cuts)) = payout.coalition {
+        for (recipient, &cut) in recipients.iter().zip(&recipient_cuts) {
+            settlement.push(recipient, cut)?;
+        }
+    } else if !payout.pay_shares {
+        settlement.push(&bucket.current_target, payout.winner_cut)?;
+    }
+
+    // Everything owed moves into the settlement; the share pool (if any) stays in the bucket
+    let owed = settlement.amounts[..settlement.payee_count as usize]
+        .iter()
+        .try_fold(0u64, |sum, &amount| sum.checked_add(amount))
+        .ok_or(HateFunError::Overflow)?;
+
+    // SAFETY: collect_payout validated the bucket holds the whole pot, and the entries are
+    // the VERIFIED split of that pot, so owed never exceeds it
+    unsafe {
+        *bucket_account.borrow_mut_lamports_unchecked() -= owed;
+        *settlement_account.borrow_mut_lamports_unchecked() += owed;
+    }
+
+    finish_payout(&pot, bucket, &payout)
+}
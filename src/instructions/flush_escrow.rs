@@ -2,91 +2,152 @@ use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvars::{clock::Clock, Sysvar},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
 use crate::{
     error::HateFunError,
-    state::{Bucket, pda},
-    verification::calculate_flush_threshold,
+    state::{Bucket, BucketAction, BucketStatus, pda},
+    verification::{
+        calculate_flush_threshold, calculate_flushable_balance, effective_status, next_status,
+        record_flip, resolve_flush_target,
+    },
 };
+use super::read_swap_guard;
 
-/// FlushEscrow instruction has no additional data
+/// FlushEscrow instruction data layout:
+/// [0]     side: u8 (0 = A, 1 = B, 2.. = further sides), the side whose escrow is passed
+/// [1..50] optional swap guard (see read_swap_guard); max_lamports is checked against 0
 pub fn process_flush_escrow(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _data: &[u8],
+    data: &[u8],
 ) -> ProgramResult {
+    // Parse instruction data
+    let &side = data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    let guard = read_swap_guard(data, 1)?;
+
     // Parse accounts
     let [bucket_account, main_bucket, escrow_to_flush] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Verify bucket is owned by program
-    if bucket_account.owner() != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+    bucket.check_side(side)?;
 
-    // Load bucket state
-    let bucket = Bucket::from_account_info(bucket_account)?;
+    // Get current epoch, slot and timestamp
+    let clock = Clock::get()?;
 
-    // Verify escrow belongs to this bucket
-    let (escrow_a_pda, _) = pda::derive_escrow_a_address(bucket_account.key(), program_id);
-    let (escrow_b_pda, _) = pda::derive_escrow_b_address(bucket_account.key(), program_id);
+    // Flips are allowed until the bucket is settled; a flip restarts the claim window
+    let status = effective_status(bucket.status()?, bucket.is_claim_window_elapsed(&clock)?);
+    let new_status = next_status(status, BucketAction::Flush)?;
 
-    let is_escrow_a = escrow_to_flush.key() == &escrow_a_pda;
-    let is_escrow_b = escrow_to_flush.key() == &escrow_b_pda;
+    // Abort if another flip landed since the caller built the transaction
+    guard.check(bucket.last_swap, &bucket.current_target, 0)?;
 
-    if !is_escrow_a && !is_escrow_b {
-        return Err(HateFunError::InvalidEscrow.into());
+    // Verify escrow is the given side's PDA for this bucket
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), side, program_id)?;
+    if escrow_to_flush.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(side).into());
     }
 
     // Verify main bucket PDA
     let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
     if main_bucket.key() != &main_bucket_pda {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(HateFunError::InvalidMainBucket.into());
     }
 
-    // Get escrow balance
-    let escrow_balance = escrow_to_flush.lamports();
+    let flushed_side = bucket.side_address(extra_sides, side)?;
+    flush_to_main(bucket, main_bucket, escrow_to_flush, side, &flushed_side, &clock, new_status)
+}
+
+/// Flip control to `flushed_side` by moving its escrow's balance above the rent-exempt
+/// reserve into the main bucket, then record the flip on the bucket
+/// Callers verify the bucket status, the escrow (side index `side`) and the main bucket PDA first
+pub(crate) fn flush_to_main(
+    bucket: &mut Bucket,
+    main_bucket: &AccountInfo,
+    escrow_to_flush: &AccountInfo,
+    side: u8,
+    flushed_side: &Pubkey,
+    clock: &Clock,
+    new_status: BucketStatus,
+) -> ProgramResult {
+    // Control goes to the side whose escrow is flushed (VERIFIED function)
+    // Flushing the controlling side's own escrow is rejected: it would not flip anything
+    let new_target = resolve_flush_target(flushed_side, &bucket.current_target)
+        .ok_or(HateFunError::SideAlreadyInControl)?;
+
+    let flushable = sweep_escrow_to_main(bucket, main_bucket, escrow_to_flush, side)?;
+
+    // Count the flip using VERIFIED function (close_bucket relies on these counters)
+    let (flip_count, total_flushed) = record_flip(bucket.flip_count, bucket.total_flushed, flushable)
+        .ok_or(HateFunError::Overflow)?;
+
+    // Update bucket state
+    bucket.current_target = new_target;
+    bucket.last_swap = flushable;
+    bucket.last_flip_epoch = clock.epoch;
+    bucket.last_flip_slot = clock.slot;
+    bucket.last_flip_timestamp = clock.unix_timestamp;
+    bucket.flip_count = flip_count;
+    bucket.total_flushed = total_flushed;
+    bucket.status = new_status as u8;
+
+    Ok(())
+}
+
+/// Move an escrow's balance above its rent-exempt reserve into the main bucket
+/// Fails unless that balance meets the bucket's flush threshold; returns the amount moved
+/// Bumps the side's flush count so deposit records can tell their pending lamports were swept,
+/// and clears the side's pending total
+pub(crate) fn sweep_escrow_to_main(
+    bucket: &mut Bucket,
+    main_bucket: &AccountInfo,
+    escrow: &AccountInfo,
+    side: u8,
+) -> Result<u64, ProgramError> {
+    // Only the balance above the escrow's rent-exempt reserve is flushable
+    // Leaving the reserve in place keeps the escrow PDA alive for later deposits
+    let rent = Rent::get()?;
+    let escrow_reserve = rent.minimum_balance(0);
+    let flushable = calculate_flushable_balance(escrow.lamports(), escrow_reserve);
 
     // Calculate required threshold using VERIFIED function
     // This is the same code Kani proved correct in src/verification.rs
     let threshold = calculate_flush_threshold(bucket.last_swap, bucket.min_increase_bps)
         .ok_or(HateFunError::Overflow)?;
 
-    // Verify escrow balance meets threshold
-    if escrow_balance < threshold {
+    // Verify flushable balance meets threshold
+    if flushable < threshold {
         return Err(HateFunError::InsufficientEscrowBalance.into());
     }
 
-    // Get current epoch
-    let clock = Clock::get()?;
-    let current_epoch = clock.epoch;
+    let main_bucket_balance = main_bucket.lamports()
+        .checked_add(flushable)
+        .ok_or(HateFunError::Overflow)?;
+
+    let side_flush_count = bucket.side_flush_counts
+        .get_mut(side as usize)
+        .ok_or(HateFunError::InvalidSide)?;
+    *side_flush_count = side_flush_count.checked_add(1).ok_or(HateFunError::Overflow)?;
+    bucket.side_flushed[side as usize] = bucket.side_flushed[side as usize]
+        .checked_add(bucket.side_pending[side as usize])
+        .ok_or(HateFunError::Overflow)?;
+    bucket.side_pending[side as usize] = 0;
 
-    // Transfer entire escrow balance to main bucket
+    // Transfer the flushable escrow balance to main bucket
     // SAFETY: These unsafe operations are justified because:
-    // 1. We've verified all account ownership and PDAs above
+    // 1. Callers have verified the escrow and main bucket PDAs
     // 2. We've calculated threshold using verified calculate_flush_threshold (no overflow)
-    // 3. We've validated escrow_balance >= threshold
+    // 3. We've validated flushable >= threshold and flushable <= escrow lamports
     // 4. The transaction is atomic - either all transfers succeed or none do
     unsafe {
-        *escrow_to_flush.borrow_mut_lamports_unchecked() = 0;
-        *main_bucket.borrow_mut_lamports_unchecked() += escrow_balance;
+        *escrow.borrow_mut_lamports_unchecked() -= flushable;
+        *main_bucket.borrow_mut_lamports_unchecked() = main_bucket_balance;
     }
 
-    // Flip current target
-    let new_target = if bucket.current_target == bucket.address_a {
-        bucket.address_b
-    } else {
-        bucket.address_a
-    };
-
-    // Update bucket state
-    bucket.current_target = new_target;
-    bucket.last_swap = escrow_balance;
-    bucket.last_flip_epoch = current_epoch;
-
-    Ok(())
+    Ok(flushable)
 }
@@ -5,17 +5,44 @@ use pinocchio::{
     ProgramResult,
 };
 
+use crate::{
+    error::HateFunError,
+    state::pda,
+    system_program,
+    verification::{SwapGuard, MAX_SIDES},
+};
+
 pub mod create_bucket;
 pub mod deposit_to_escrow;
 pub mod flush_escrow;
 pub mod claim_payout;
 pub mod close_bucket;
+pub mod deposit_and_flush;
+pub mod reinforce;
+pub mod refund_pending;
+pub mod withdraw_pending;
+pub mod claim_share;
+pub mod create_side_config;
+pub mod finalize;
+pub mod withdraw;
+pub mod close_settled;
+pub mod close_deposit_record;
 
 use create_bucket::process_create_bucket;
 use deposit_to_escrow::process_deposit_to_escrow;
 use flush_escrow::process_flush_escrow;
 use claim_payout::process_claim_payout;
 use close_bucket::process_close_bucket;
+use deposit_and_flush::process_deposit_and_flush;
+use reinforce::process_reinforce;
+use refund_pending::process_refund_pending;
+use withdraw_pending::process_withdraw_pending;
+use claim_share::process_claim_share;
+use create_side_config::process_create_side_config;
+use finalize::process_finalize;
+use withdraw::process_withdraw;
+use close_settled::process_close_settled;
+use close_deposit_record::process_close_deposit_record;
 
 /// Instruction discriminators
 #[repr(u8)]
@@ -25,6 +52,16 @@ pub enum GateInstruction {
     FlushEscrow = 2,
     ClaimPayout = 3,
     CloseBucket = 4,
+    DepositAndFlush = 5,
+    Reinforce = 6,
+    RefundPending = 7,
+    WithdrawPending = 8,
+    ClaimShare = 9,
+    CreateSideConfig = 10,
+    Finalize = 11,
+    Withdraw = 12,
+    CloseSettled = 13,
+    CloseDepositRecord = 14,
 }
 
 /// Main instruction processor
@@ -43,10 +80,140 @@ pub fn process_instruction(
         2 => process_flush_escrow(program_id, accounts, &instruction_data[1..]),
         3 => process_claim_payout(program_id, accounts, &instruction_data[1..]),
         4 => process_close_bucket(program_id, accounts, &instruction_data[1..]),
+        5 => process_deposit_and_flush(program_id, accounts, &instruction_data[1..]),
+        6 => process_reinforce(program_id, accounts, &instruction_data[1..]),
+        7 => process_refund_pending(program_id, accounts, &instruction_data[1..]),
+        8 => process_withdraw_pending(program_id, accounts, &instruction_data[1..]),
+        9 => process_claim_share(program_id, accounts, &instruction_data[1..]),
+        10 => process_create_side_config(program_id, accounts, &instruction_data[1..]),
+        11 => process_finalize(program_id, accounts, &instruction_data[1..]),
+        12 => process_withdraw(program_id, accounts, &instruction_data[1..]),
+        13 => process_close_settled(program_id, accounts, &instruction_data[1..]),
+        14 => process_close_deposit_record(program_id, accounts, &instruction_data[1..]),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
 
+/// Close a program-owned account whose lamports have already been moved out
+/// Wipes its data, shrinks it to zero bytes and hands it back to the System Program,
+/// so nothing can read the old state later in the same transaction or CPI chain
+pub fn close_account(account: &AccountInfo) -> ProgramResult {
+    account.try_borrow_mut_data()?.fill(0);
+    account.resize(0)?;
+
+    // SAFETY: No references to the account owner are held at this point
+    unsafe {
+        account.assign(&system_program::ID);
+    }
+
+    Ok(())
+}
+
+/// Verify the trailing escrow accounts of sides 2 and up, in side order
+/// Returns exactly `side_count - 2` escrows; extra trailing accounts are ignored
+pub fn verify_extra_escrows<'a>(
+    bucket: &Pubkey,
+    side_count: u8,
+    accounts: &'a [AccountInfo],
+    program_id: &Pubkey,
+) -> Result<&'a [AccountInfo], ProgramError> {
+    let escrows = accounts
+        .get(..(side_count as usize).saturating_sub(2))
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    for (index, escrow) in escrows.iter().enumerate() {
+        let (escrow_pda, _) = pda::derive_escrow_address(bucket, index as u8 + 2, program_id)?;
+        if escrow.key() != &escrow_pda {
+            return Err(HateFunError::InvalidEscrow.into());
+        }
+    }
+
+    Ok(escrows)
+}
+
+/// Verify the trailing SideConfig accounts of the configured sides, in side order
+/// Returns exactly one config per bit of `side_config_mask`; extra trailing accounts are ignored
+pub fn verify_side_configs<'a>(
+    bucket: &Pubkey,
+    side_config_mask: u16,
+    accounts: &'a [AccountInfo],
+    program_id: &Pubkey,
+) -> Result<&'a [AccountInfo], ProgramError> {
+    let side_configs = accounts
+        .get(..side_config_mask.count_ones() as usize)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let configured_sides = (0..MAX_SIDES).filter(|side| side_config_mask & (1 << side) != 0);
+    for (side, side_config) in configured_sides.zip(side_configs) {
+        let (side_config_pda, _) = pda::derive_side_config_address(bucket, side, program_id);
+        if side_config.key() != &side_config_pda {
+            return Err(HateFunError::InvalidSideConfig.into());
+        }
+    }
+
+    Ok(side_configs)
+}
+
+/// Hand the rent of the bucket's SideConfigs back to `recipient` and close them
+/// Configs no longer owned by the program are skipped
+pub fn close_side_configs(side_configs: &[AccountInfo], recipient: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    for side_config in side_configs.iter().filter(|side_config| side_config.owner() == program_id) {
+        // SAFETY: The config is a program-owned PDA verified by verify_side_configs,
+        // and it is zeroed before the recipient is credited
+        unsafe {
+            let lamports = side_config.lamports();
+            *side_config.borrow_mut_lamports_unchecked() = 0;
+            *recipient.borrow_mut_lamports_unchecked() = recipient.lamports()
+                .checked_add(lamports)
+                .ok_or(HateFunError::Overflow)?;
+        }
+        close_account(side_config)?;
+    }
+
+    Ok(())
+}
+
+/// Swap guard flags: which optional preconditions the trailing guard carries
+pub const GUARD_LAST_SWAP: u8 = 1 << 0;
+pub const GUARD_CURRENT_TARGET: u8 = 1 << 1;
+pub const GUARD_MAX_LAMPORTS: u8 = 1 << 2;
+
+/// Size of the optional trailing swap guard
+pub const SWAP_GUARD_LEN: usize = 49;
+
+/// Read the optional swap guard appended after an instruction's own data
+/// Layout (relative to `offset`):
+/// [0]      flags: u8 (GUARD_* bits)
+/// [1..9]   expected_last_swap: u64
+/// [9..41]  expected_current_target: Pubkey
+/// [41..49] max_lamports: u64
+/// No trailing bytes means no guard; fields whose flag is clear are ignored
+pub fn read_swap_guard(data: &[u8], offset: usize) -> Result<SwapGuard, ProgramError> {
+    if data.len() <= offset {
+        return Ok(SwapGuard::default());
+    }
+    if data.len() < offset + SWAP_GUARD_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let flags = data[offset];
+    if flags & !(GUARD_LAST_SWAP | GUARD_CURRENT_TARGET | GUARD_MAX_LAMPORTS) != 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(SwapGuard {
+        expected_last_swap: (flags & GUARD_LAST_SWAP != 0)
+            .then(|| read_u64(data, offset + 1))
+            .transpose()?,
+        expected_current_target: (flags & GUARD_CURRENT_TARGET != 0)
+            .then(|| read_pubkey(data, offset + 9))
+            .transpose()?,
+        max_lamports: (flags & GUARD_MAX_LAMPORTS != 0)
+            .then(|| read_u64(data, offset + 41))
+            .transpose()?,
+    })
+}
+
 /// Helper function to read u64 from little-endian bytes
 pub fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
     if data.len() < offset + 8 {
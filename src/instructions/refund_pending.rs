@@ -0,0 +1,96 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, DepositRecord, RefundPolicy, pda},
+    verification::{
+        calculate_flushable_balance, calculate_pro_rata_share, next_status, settle_deposit_record,
+    },
+};
+
+/// RefundPending instruction data layout: none
+///
+/// Accounts: bucket, the record's escrow, deposit record, depositor (receives the refund)
+/// Permissionless: anyone can push a depositor's unflushed deposits back to them once the bucket is settled
+pub fn process_refund_pending(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [bucket_account, escrow, deposit_record, depositor] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state (verifies owner, size, discriminator and version)
+    let bucket = Bucket::load_mut(bucket_account, program_id)?;
+
+    if bucket.refund_policy()? != RefundPolicy::Refund {
+        return Err(HateFunError::RefundsDisabled.into());
+    }
+
+    // Refunds only open up once the claim has paid out the main bucket
+    next_status(bucket.status()?, BucketAction::Refund)?;
+
+    // Verify the record belongs to this bucket (not an earlier one at its address) and depositor,
+    // and the escrow to the record's side
+    let record = DepositRecord::load_mut(deposit_record, program_id)?;
+    if !record.belongs_to(bucket_account.key(), bucket) {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    if &record.depositor != depositor.key() {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), record.side, program_id)?;
+    if escrow.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(record.side).into());
+    }
+
+    // Bring the record up to date: anything swept by a later flush already went to the winner
+    let side = record.side as usize;
+    let side_flush_count = *bucket.side_flush_counts.get(side).ok_or(HateFunError::InvalidSide)?;
+    let (total_flushed, pending) = settle_deposit_record(
+        record.total_flushed,
+        record.pending,
+        record.flush_count,
+        side_flush_count,
+    ).ok_or(HateFunError::Overflow)?;
+
+    if pending == 0 {
+        return Err(HateFunError::NothingToRefund.into());
+    }
+
+    // Share of the escrow above its rent-exempt reserve using VERIFIED function
+    // Kani proved the refund never exceeds the escrow's refundable balance
+    let rent = Rent::get()?;
+    let refundable = calculate_flushable_balance(escrow.lamports(), rent.minimum_balance(0));
+    let side_pending = bucket.side_pending[side];
+    let refund = calculate_pro_rata_share(refundable, pending, side_pending);
+
+    // SAFETY: The escrow is a program-owned PDA verified above, and refund <= refundable
+    // keeps it at or above its rent-exempt reserve
+    unsafe {
+        *escrow.borrow_mut_lamports_unchecked() -= refund;
+        *depositor.borrow_mut_lamports_unchecked() = depositor.lamports()
+            .checked_add(refund)
+            .ok_or(HateFunError::Overflow)?;
+    }
+
+    record.total_flushed = total_flushed;
+    record.pending = 0;
+    record.flush_count = side_flush_count;
+    record.total_refunded = record.total_refunded
+        .checked_add(refund)
+        .ok_or(HateFunError::Overflow)?;
+    bucket.side_pending[side] = side_pending.saturating_sub(pending);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, pda},
+    verification::{effective_status, next_status, record_reinforce},
+};
+use super::flush_escrow::sweep_escrow_to_main;
+use super::read_swap_guard;
+
+/// Reinforce instruction data layout:
+/// [0]     side: u8 (0 = A, 1 = B, 2.. = further sides), the side whose escrow is passed
+/// [1..50] optional swap guard (see read_swap_guard); max_lamports is checked against 0
+pub fn process_reinforce(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data
+    let &side = data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    let guard = read_swap_guard(data, 1)?;
+
+    // Parse accounts
+    let [bucket_account, main_bucket, escrow_to_reinforce] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Load bucket state and side addresses (verifies owner, size, discriminator and version)
+    let (bucket, extra_sides) = Bucket::load_mut_with_sides(bucket_account, program_id)?;
+    bucket.check_side(side)?;
+
+    // Get current epoch, slot and timestamp
+    let clock = Clock::get()?;
+
+    // Reinforcing is allowed until the bucket is settled; it does not restart the claim window
+    let status = effective_status(bucket.status()?, bucket.is_claim_window_elapsed(&clock)?);
+    next_status(status, BucketAction::Reinforce)?;
+
+    // Abort if another flip landed since the caller built the transaction
+    guard.check(bucket.last_swap, &bucket.current_target, 0)?;
+
+    // Verify escrow is the given side's PDA for this bucket
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), side, program_id)?;
+    if escrow_to_reinforce.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(side).into());
+    }
+    let reinforced_side = bucket.side_address(extra_sides, side)?;
+
+    // Verify main bucket PDA
+    let (main_bucket_pda, _) = pda::derive_main_bucket_address(bucket_account.key(), program_id);
+    if main_bucket.key() != &main_bucket_pda {
+        return Err(HateFunError::InvalidMainBucket.into());
+    }
+
+    // Only the side in control can reinforce; the challenger has to flush instead
+    if reinforced_side != bucket.current_target {
+        return Err(HateFunError::SideNotInControl.into());
+    }
+
+    // Same threshold as a flip: the reinforcement must beat last_swap by min_increase_bps
+    let reinforced = sweep_escrow_to_main(bucket, main_bucket, escrow_to_reinforce, side)?;
+
+    // Count the reinforcement using VERIFIED function (it blocks close like a flip does)
+    let (reinforce_count, total_flushed) = record_reinforce(bucket.reinforce_count, bucket.total_flushed, reinforced)
+        .ok_or(HateFunError::Overflow)?;
+
+    // Raise the bar for the challenger; current_target, flip_count and the claim window are untouched
+    bucket.last_swap = reinforced;
+    bucket.reinforce_count = reinforce_count;
+    bucket.total_flushed = total_flushed;
+
+    Ok(())
+}
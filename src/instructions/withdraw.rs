@@ -0,0 +1,95 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::Settlement,
+};
+use super::close_account;
+
+/// Withdraw instruction has no additional data
+/// Accounts: payee, settlement, claimer (the settlement's entry 1, receives its rent on close),
+/// then optionally a destination
+///
+/// Permissionless: anyone can push every unwithdrawn settlement entry owed to the payee to the
+/// payee's own account. A payee that cannot receive lamports directly signs and names a
+/// destination instead. Once every entry is paid the settlement closes to the claimer
+///
+/// An entry whose payee can neither receive lamports nor sign is never paid, so its settlement
+/// never closes and the claimer's rent stays locked; nothing else can collect or reassign it
+pub fn process_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Parse accounts
+    let [payee, settlement_account, claimer, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Only the payee may redirect their entries
+    let destination = match remaining.first() {
+        Some(destination) => {
+            if !payee.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            destination
+        }
+        None => payee,
+    };
+
+    // Paying the settlement to itself would mark entries withdrawn without moving anything
+    if destination.key() == settlement_account.key() {
+        return Err(HateFunError::InvalidDestination.into());
+    }
+
+    // Load settlement (verifies owner, size, discriminator and version)
+    let settlement = Settlement::load_mut(settlement_account, program_id)?;
+
+    if claimer.key() != &settlement.payees[Settlement::CLAIMER_INDEX] {
+        return Err(HateFunError::InvalidSettlement.into());
+    }
+
+    // Collect the payee's entries (the creator may also have finalized, holding two entries)
+    let mut amount: u64 = 0;
+    let payee_count = (settlement.payee_count as usize).min(Settlement::MAX_PAYEES);
+    for index in 0..payee_count {
+        if &settlement.payees[index] == payee.key() && settlement.withdrawn[index] == 0 {
+            amount = amount.checked_add(settlement.amounts[index]).ok_or(HateFunError::Overflow)?;
+            settlement.withdrawn[index] = 1;
+        }
+    }
+
+    if amount == 0 {
+        return Err(HateFunError::NothingToCollect.into());
+    }
+
+    // SAFETY: The settlement is a program-owned PDA holding every unwithdrawn entry on top of
+    // its rent, each entry is paid at most once and the destination is a different account
+    unsafe {
+        *settlement_account.borrow_mut_lamports_unchecked() -= amount;
+        *destination.borrow_mut_lamports_unchecked() = destination.lamports()
+            .checked_add(amount)
+            .ok_or(HateFunError::Overflow)?;
+    }
+
+    if !settlement.is_fully_withdrawn() {
+        return Ok(());
+    }
+
+    // Last entry paid: only the claimer's rent is left
+    // SAFETY: The settlement is a program-owned PDA verified above; the claimer was checked
+    // against its recorded entry
+    unsafe {
+        *claimer.borrow_mut_lamports_unchecked() = claimer.lamports()
+            .checked_add(settlement_account.lamports())
+            .ok_or(HateFunError::Overflow)?;
+        *settlement_account.borrow_mut_lamports_unchecked() = 0;
+    }
+
+    close_account(settlement_account)
+}
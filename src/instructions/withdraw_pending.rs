@@ -0,0 +1,117 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::HateFunError,
+    state::{Bucket, BucketAction, DepositRecord, pda},
+    verification::{
+        calculate_flushable_balance, calculate_withdrawable, effective_status, next_status,
+        settle_deposit_record,
+    },
+};
+use super::read_u64;
+
+/// WithdrawPending instruction data layout:
+/// [0..8] amount: u64 (0 = everything withdrawable)
+///
+/// Accounts: depositor (signer), bucket, the record's escrow, depositor's DepositRecord
+pub fn process_withdraw_pending(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data
+    let requested_amount = read_u64(data, 0)?;
+
+    // Parse accounts
+    let [depositor, bucket_account, escrow, deposit_record] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Verify signer
+    if !depositor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Load bucket state (verifies owner, size, discriminator and version)
+    let bucket = Bucket::load_mut(bucket_account, program_id)?;
+
+    if bucket.withdrawals_disabled != 0 {
+        return Err(HateFunError::WithdrawalsDisabled.into());
+    }
+
+    // Withdrawing is allowed until the bucket is settled or closed
+    let clock = Clock::get()?;
+    let status = effective_status(bucket.status()?, bucket.is_claim_window_elapsed(&clock)?);
+    next_status(status, BucketAction::Withdraw)?;
+
+    // Verify the record is the depositor's and the escrow belongs to the record's side
+    let record = DepositRecord::load_mut(deposit_record, program_id)?;
+    let (record_pda, _) = pda::derive_deposit_record_address(
+        bucket_account.key(),
+        record.side,
+        depositor.key(),
+        program_id,
+    );
+    if deposit_record.key() != &record_pda {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    // A record left by an earlier bucket at this address holds nothing in this bucket's escrow
+    if !record.belongs_to(bucket_account.key(), bucket) {
+        return Err(HateFunError::InvalidDepositRecord.into());
+    }
+
+    let (escrow_pda, _) = pda::derive_escrow_address(bucket_account.key(), record.side, program_id)?;
+    if escrow.key() != &escrow_pda {
+        return Err(HateFunError::invalid_escrow_for_side(record.side).into());
+    }
+
+    // Bring the record up to date: anything swept by a later flush is no longer withdrawable
+    let side = record.side as usize;
+    let side_flush_count = *bucket.side_flush_counts.get(side).ok_or(HateFunError::InvalidSide)?;
+    let (total_flushed, pending) = settle_deposit_record(
+        record.total_flushed,
+        record.pending,
+        record.flush_count,
+        side_flush_count,
+    ).ok_or(HateFunError::Overflow)?;
+
+    // Bound the withdrawal using VERIFIED function
+    // Kani proved it never exceeds the receipt or dips into the escrow's rent-exempt reserve
+    let rent = Rent::get()?;
+    let flushable = calculate_flushable_balance(escrow.lamports(), rent.minimum_balance(0));
+    let withdrawable = calculate_withdrawable(pending, flushable);
+    if withdrawable == 0 {
+        return Err(HateFunError::NothingToWithdraw.into());
+    }
+
+    let amount = if requested_amount == 0 { withdrawable } else { requested_amount };
+    if amount > withdrawable {
+        return Err(HateFunError::WithdrawTooLarge.into());
+    }
+
+    // SAFETY: The escrow is a program-owned PDA verified above, and amount <= flushable
+    // keeps it at or above its rent-exempt reserve
+    unsafe {
+        *escrow.borrow_mut_lamports_unchecked() -= amount;
+        *depositor.borrow_mut_lamports_unchecked() = depositor.lamports()
+            .checked_add(amount)
+            .ok_or(HateFunError::Overflow)?;
+    }
+
+    record.total_flushed = total_flushed;
+    record.pending = pending - amount;
+    record.flush_count = side_flush_count;
+    record.total_withdrawn = record.total_withdrawn
+        .checked_add(amount)
+        .ok_or(HateFunError::Overflow)?;
+    bucket.side_pending[side] = bucket.side_pending[side].saturating_sub(amount);
+
+    Ok(())
+}
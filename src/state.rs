@@ -1,28 +1,209 @@
 use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
-    pubkey::Pubkey,
+    pubkey::{create_program_address, Pubkey},
+    sysvars::clock::Clock,
+};
+
+use crate::{
+    error::HateFunError,
+    verification::{
+        calculate_claim_deadline, is_claim_window_elapsed, settle_deposit_record, validate_side_count,
+        MAX_SIDES, MAX_SIDE_RECIPIENTS,
+    },
 };
 
 /// The main Bucket account that stores all parameters and state
+/// Sides 0 and 1 live in the header; buckets with more sides store the remaining
+/// addresses right after it as `[Pubkey; side_count - 2]` (see Bucket::space)
 #[repr(C)]
 pub struct Bucket {
+    pub discriminator: [u8; 8],      // 8 bytes - Account type tag (Bucket::DISCRIMINATOR)
+    pub version: u8,                 // 1 byte - Layout version (Bucket::VERSION)
+    pub bump: u8,                    // 1 byte - PDA bump seed
+    pub status: u8,                  // 1 byte - Lifecycle phase (BucketStatus)
+    pub claim_delay_unit: u8,        // 1 byte - Unit of claim_delay (ClaimDelayUnit)
+    pub side_count: u8,              // 1 byte - Number of competing sides (2-16)
+    pub refund_policy: u8,           // 1 byte - What happens to unflushed escrow balances at claim (RefundPolicy)
+    pub withdrawals_disabled: u8,    // 1 byte - 1 if depositors cannot withdraw pending deposits
+    pub payout_mode: u8,             // 1 byte - Who receives the winner's cut (PayoutMode)
     pub address_a: Pubkey,           // 32 bytes - First competing address
     pub address_b: Pubkey,           // 32 bytes - Second competing address
-    pub creator_address: Pubkey,     // 32 bytes - Receives creator fee
-    pub current_target: Pubkey,      // 32 bytes - Current winner (A or B)
+    pub creator_address: Pubkey,     // 32 bytes - Receives creator fee; signed the creation
+    pub seed: [u8; 32],              // 32 bytes - Seed the bucket PDA was derived from with creator_address
+    pub payer: Pubkey,               // 32 bytes - Funded the PDAs' rent; gets the reserves back at settlement
+    pub current_target: Pubkey,      // 32 bytes - Current winner (address of the side in control)
     pub last_swap: u64,              // 8 bytes - Amount needed to be exceeded
     pub creation_epoch: u64,         // 8 bytes - Epoch when bucket was created
+    pub creation_slot: u64,          // 8 bytes - Slot when bucket was created; tells a re-created bucket apart from earlier ones at its address
     pub last_flip_epoch: u64,        // 8 bytes - Last epoch when target flipped
+    pub flip_count: u64,             // 8 bytes - Number of successful flips
+    pub total_flushed: u64,          // 8 bytes - Lamports flushed into main bucket
+    pub claim_delay: u64,            // 8 bytes - Time without a flip before payout can be claimed
+    pub last_flip_slot: u64,         // 8 bytes - Last slot when target flipped
+    pub last_flip_timestamp: i64,    // 8 bytes - Unix timestamp of the last flip
+    pub min_deposit: u64,            // 8 bytes - Smallest accepted deposit
+    pub min_initial_swap: u64,       // 8 bytes - Floor the initial last_swap was validated against
+    pub reinforce_count: u64,        // 8 bytes - Number of reinforcements by the controlling side
+    pub side_flush_counts: [u64; MAX_SIDES as usize], // 128 bytes - Times each side's escrow was swept into the main bucket
+    pub side_pending: [u64; MAX_SIDES as usize],      // 128 bytes - Recorded deposits per side since its last sweep
+    pub side_flushed: [u64; MAX_SIDES as usize],      // 128 bytes - Recorded deposits per side swept into the main bucket
+    pub share_pool: u64,             // 8 bytes - Winner's cut still held for ClaimShare (proportional mode)
+    pub unclaimed_contributions: u64, // 8 bytes - Winning side's flushed contributions whose share is unclaimed
+    pub start_slot: u64,             // 8 bytes - Slot of the SlotHashes entry a random start was drawn from (0 if chosen)
+    pub start_slot_hash: [u8; 32],   // 32 bytes - Hash of that entry, the randomness source (zero if chosen)
     pub creator_fee_bps: u16,        // 2 bytes - Creator fee in basis points
     pub claimer_fee_bps: u16,        // 2 bytes - Claimer fee in basis points
     pub min_increase_bps: u16,       // 2 bytes - Minimum increase percentage
-    pub bump: u8,                    // 1 byte - PDA bump seed
+    pub side_config_mask: u16,       // 2 bytes - Bit n set if side n splits its payout through a SideConfig
+    pub starting_side: u8,           // 1 byte - Side in control at creation
+    pub start_source: u8,            // 1 byte - How starting_side was picked (StartSource)
+    pub _padding: [u8; 2],           // 2 bytes - Keeps deposit_record_count 4-byte aligned
+    pub deposit_record_count: u32,   // 4 bytes - DepositRecords created for this bucket (saturating)
+}
+
+// The account layout must match the in-memory struct exactly (no hidden padding)
+const _: () = assert!(Bucket::SIZE == core::mem::size_of::<Bucket>());
+
+/// Lifecycle phase of a bucket
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketStatus {
+    /// Accepting deposits and flips
+    Open = 0,
+    /// Claim window has elapsed since the last flip; payout can be claimed
+    Claimable = 1,
+    /// Payout has been distributed
+    Settled = 2,
+    /// Closed by the creator before any flip, or closed once settled and drained
+    Closed = 3,
+}
+
+impl TryFrom<u8> for BucketStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BucketStatus::Open),
+            1 => Ok(BucketStatus::Claimable),
+            2 => Ok(BucketStatus::Settled),
+            3 => Ok(BucketStatus::Closed),
+            _ => Err(HateFunError::InvalidBucketStatus.into()),
+        }
+    }
+}
+
+/// Unit in which a bucket's claim delay is measured
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimDelayUnit {
+    /// Clock::epoch
+    Epochs = 0,
+    /// Clock::slot
+    Slots = 1,
+    /// Clock::unix_timestamp (wall-clock seconds)
+    Seconds = 2,
+}
+
+impl TryFrom<u8> for ClaimDelayUnit {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ClaimDelayUnit::Epochs),
+            1 => Ok(ClaimDelayUnit::Slots),
+            2 => Ok(ClaimDelayUnit::Seconds),
+            _ => Err(HateFunError::InvalidClaimDelay.into()),
+        }
+    }
+}
+
+/// What a claim does with escrow balances that were never flushed
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundPolicy {
+    /// Pending escrow balances are swept into the payout
+    Sweep = 0,
+    /// Pending escrow balances stay in the escrows and are refunded to depositors
+    Refund = 1,
+}
+
+impl TryFrom<u8> for RefundPolicy {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RefundPolicy::Sweep),
+            1 => Ok(RefundPolicy::Refund),
+            _ => Err(HateFunError::InvalidRefundPolicy.into()),
+        }
+    }
+}
+
+/// Who receives the winner's cut of the pot
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutMode {
+    /// The address of the side in control receives it
+    Winner = 0,
+    /// Contributors of the side in control share it pro rata through ClaimShare
+    Proportional = 1,
+}
+
+impl TryFrom<u8> for PayoutMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PayoutMode::Winner),
+            1 => Ok(PayoutMode::Proportional),
+            _ => Err(HateFunError::InvalidPayoutMode.into()),
+        }
+    }
+}
+
+/// How a bucket's starting side was picked
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartSource {
+    /// The creator chose the side (side A unless given)
+    Creator = 0,
+    /// Drawn from the most recent SlotHashes entry at creation (the creator can grind it)
+    SlotHashes = 1,
+}
+
+/// Instructions that drive a bucket through its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketAction {
+    Deposit,
+    Flush,
+    Reinforce,
+    Claim,
+    Close,
+    Refund,
+    Withdraw,
+    ClaimShare,
+    CloseSettled,
+    ConfigureSides,
 }
 
 impl Bucket {
     /// Size of Bucket account in bytes
-    pub const SIZE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 2 + 2 + 2 + 1;
+    pub const SIZE: usize = 8 + 1 + 1 + 1 + 1 + 4 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 * MAX_SIDES as usize + 8 * MAX_SIDES as usize + 8 * MAX_SIDES as usize + 8 + 8 + 8 + 32 + 2 + 2 + 2 + 2 + 1 + 1 + 2 + 4;
+
+    /// Account discriminator stored in the first 8 bytes
+    pub const DISCRIMINATOR: [u8; 8] = *b"HFBUCKET";
+
+    /// CreateBucket's starting_side value that draws the side from SlotHashes
+    pub const STARTING_SIDE_RANDOM: u8 = u8::MAX;
+
+    /// Current account layout version
+    /// Every account type bumps its version on each change to its field layout, so accounts
+    /// written by an older build fail to load (see "Account Versions" in the README)
+    /// 1: first layout (two sides, no deposit tracking)
+    /// 2: adds the seed and payer, extra sides, deposit tracking, refund and payout modes,
+    ///    the starting side and the creation slot
+    pub const VERSION: u8 = 2;
 
     /// Seed prefix for Bucket PDA
     pub const SEED_PREFIX: &'static [u8] = b"bucket";
@@ -36,16 +217,527 @@ impl Bucket {
     /// Seed prefix for escrow B PDA
     pub const ESCROW_B_SEED_PREFIX: &'static [u8] = b"escrow_b";
 
-    /// Deserialize a Bucket from account data
-    pub fn from_account_info(account: &AccountInfo) -> Result<&mut Self, ProgramError> {
-        let data = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
-        Ok(data)
+    /// Seed prefix for the escrow PDAs of sides 2 and up (followed by the side index byte)
+    pub const ESCROW_SEED_PREFIX: &'static [u8] = b"escrow";
+
+    /// Side index of address A
+    pub const SIDE_A: u8 = 0;
+
+    /// Side index of address B
+    pub const SIDE_B: u8 = 1;
+
+    /// Stored lifecycle status
+    pub fn status(&self) -> Result<BucketStatus, ProgramError> {
+        BucketStatus::try_from(self.status)
+    }
+
+    /// Stored refund policy
+    pub fn refund_policy(&self) -> Result<RefundPolicy, ProgramError> {
+        RefundPolicy::try_from(self.refund_policy)
+    }
+
+    /// Stored payout mode
+    pub fn payout_mode(&self) -> Result<PayoutMode, ProgramError> {
+        PayoutMode::try_from(self.payout_mode)
+    }
+
+    /// Account size of a bucket with `side_count` sides
+    pub const fn space(side_count: u8) -> usize {
+        Self::SIZE + 32 * (side_count as usize).saturating_sub(2)
+    }
+
+    /// Verify a side index exists on this bucket
+    pub fn check_side(&self, side: u8) -> Result<(), ProgramError> {
+        if side >= self.side_count {
+            return Err(HateFunError::InvalidSide.into());
+        }
+        Ok(())
+    }
+
+    /// Address competing on the given side
+    /// `extra_sides` is the trailing address array returned by Bucket::load_mut_with_sides
+    pub fn side_address(&self, extra_sides: &[Pubkey], side: u8) -> Result<Pubkey, ProgramError> {
+        self.check_side(side)?;
+        match side {
+            Self::SIDE_A => Ok(self.address_a),
+            Self::SIDE_B => Ok(self.address_b),
+            _ => extra_sides
+                .get(side as usize - 2)
+                .copied()
+                .ok_or(HateFunError::InvalidSide.into()),
+        }
     }
 
-    /// Deserialize a Bucket from account data (immutable)
-    pub fn from_account_info_unchecked(account: &AccountInfo) -> Result<&Self, ProgramError> {
-        let data = unsafe { &*account.borrow_data_unchecked().as_ptr().cast::<Self>() };
-        Ok(data)
+    /// Side whose address is `address`, if any (side addresses are unique)
+    pub fn find_side(&self, extra_sides: &[Pubkey], address: &Pubkey) -> Option<u8> {
+        (0..self.side_count).find(|&side| self.side_address(extra_sides, side).ok().as_ref() == Some(address))
+    }
+
+    /// Whether this bucket lives at the PDA its creator_address, seed and stored bump derive
+    /// CreateBucket requires creator_address to sign, so a match proves the creator authorized it
+    /// One create_program_address instead of a bump search
+    pub fn is_authorized_by_creator(&self, bucket_key: &Pubkey, program_id: &Pubkey) -> bool {
+        self.is_authorized_by_creator_with(bucket_key, |seeds| create_program_address(seeds, program_id).ok())
+    }
+
+    /// is_authorized_by_creator with the address derivation passed in
+    /// pinocchio's syscall only exists on-chain, so host code and tests supply their own
+    pub fn is_authorized_by_creator_with(
+        &self,
+        bucket_key: &Pubkey,
+        create_address: impl FnOnce(&[&[u8]]) -> Option<Pubkey>,
+    ) -> bool {
+        let bump = [self.bump];
+        create_address(&[Bucket::SEED_PREFIX, self.creator_address.as_ref(), self.seed.as_ref(), &bump])
+            .is_some_and(|bucket_pda| &bucket_pda == bucket_key)
+    }
+
+    /// Whether the side splits its payout among the recipients of a SideConfig
+    pub fn has_side_config(&self, side: u8) -> bool {
+        side < MAX_SIDES && self.side_config_mask & (1 << side) != 0
+    }
+
+    /// Time of the last flip in the bucket's claim delay unit
+    /// Timestamps before the Unix epoch are clamped to zero
+    pub fn last_flip_time(&self) -> Result<u64, ProgramError> {
+        Ok(match ClaimDelayUnit::try_from(self.claim_delay_unit)? {
+            ClaimDelayUnit::Epochs => self.last_flip_epoch,
+            ClaimDelayUnit::Slots => self.last_flip_slot,
+            ClaimDelayUnit::Seconds => self.last_flip_timestamp.max(0) as u64,
+        })
+    }
+
+    /// Current time in the bucket's claim delay unit
+    pub fn current_time(&self, clock: &Clock) -> Result<u64, ProgramError> {
+        Ok(match ClaimDelayUnit::try_from(self.claim_delay_unit)? {
+            ClaimDelayUnit::Epochs => clock.epoch,
+            ClaimDelayUnit::Slots => clock.slot,
+            ClaimDelayUnit::Seconds => clock.unix_timestamp.max(0) as u64,
+        })
+    }
+
+    /// Earliest epoch, slot or Unix timestamp (per claim delay unit) at which the payout can be claimed
+    pub fn claim_deadline(&self) -> Result<u64, ProgramError> {
+        calculate_claim_deadline(self.last_flip_time()?, self.claim_delay)
+            .ok_or(HateFunError::Overflow.into())
+    }
+
+    /// Check whether the claim window has elapsed since the last flip
+    /// Measured in the bucket's claim delay unit using the VERIFIED deadline computation
+    pub fn is_claim_window_elapsed(&self, clock: &Clock) -> Result<bool, ProgramError> {
+        Ok(is_claim_window_elapsed(self.current_time(clock)?, self.last_flip_time()?, self.claim_delay))
+    }
+
+    /// Validate raw bucket account data: discriminator, layout version, side count
+    /// and exact size for that side count
+    pub fn check_data(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::SIZE {
+            return Err(HateFunError::InvalidBucketSize.into());
+        }
+        if data[..8] != Self::DISCRIMINATOR {
+            return Err(HateFunError::InvalidBucketDiscriminator.into());
+        }
+        if data[8] != Self::VERSION {
+            return Err(HateFunError::UnsupportedBucketVersion.into());
+        }
+        let side_count = data[core::mem::offset_of!(Bucket, side_count)];
+        if !validate_side_count(side_count) {
+            return Err(HateFunError::InvalidSideCount.into());
+        }
+        if data.len() != Self::space(side_count) {
+            return Err(HateFunError::InvalidBucketSize.into());
+        }
+        Ok(())
+    }
+
+    /// Decode the fixed bucket header from raw account data (client-side tooling)
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_data(data)?;
+        // SAFETY: Size is checked above; read_unaligned copes with any buffer alignment
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr().cast::<Self>()) })
+    }
+
+    /// Validate that an account holds a Bucket owned by this program
+    fn check_account(account: &AccountInfo, program_id: &Pubkey) -> Result<(), ProgramError> {
+        if account.owner() != program_id {
+            return Err(HateFunError::InvalidBucketOwner.into());
+        }
+        // SAFETY: Read-only look at the data before the calling loader hands out its reference;
+        // callers never use an earlier reference into the same account after loading again
+        // (load_mut_with_sides hands out the header and the side list from one borrow)
+        Self::check_data(unsafe { account.borrow_data_unchecked() })
+    }
+
+    /// Load a validated Bucket from account data (immutable)
+    pub fn load<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a Self, ProgramError> {
+        Self::check_account(account, program_id)?;
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        Ok(unsafe { &*account.borrow_data_unchecked().as_ptr().cast::<Self>() })
+    }
+
+    /// Load a validated Bucket from account data (mutable)
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_mut<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        Self::check_account(account, program_id)?;
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        Ok(unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() })
+    }
+
+    /// Load a validated Bucket (mutable) together with the addresses of sides 2 and up
+    /// (empty for a two-sided bucket), split from a single borrow of the account data
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_mut_with_sides<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<(&'a mut Self, &'a [Pubkey]), ProgramError> {
+        Self::check_account(account, program_id)?;
+        // SAFETY: One mutable borrow, split into the header and the disjoint side list.
+        // The size check above guarantees a whole number of addresses after the header,
+        // the runtime aligns account data to 8 bytes and Pubkey has alignment 1
+        let (header, sides) = unsafe { account.borrow_mut_data_unchecked() }.split_at_mut(Self::SIZE);
+        let bucket = unsafe { &mut *header.as_mut_ptr().cast::<Self>() };
+        let sides = unsafe { core::slice::from_raw_parts(sides.as_ptr().cast::<Pubkey>(), sides.len() / 32) };
+        Ok((bucket, sides))
+    }
+
+    /// Load the trailing addresses of sides 2 and up (mutable, used at creation)
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_sides_mut<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut [Pubkey], ProgramError> {
+        Self::check_account(account, program_id)?;
+        // SAFETY: Same layout argument as load_mut_with_sides; the range is disjoint from the header
+        Ok(unsafe {
+            let data = account.borrow_mut_data_unchecked();
+            let len = (data.len() - Self::SIZE) / 32;
+            core::slice::from_raw_parts_mut(data[Self::SIZE..].as_mut_ptr().cast::<Pubkey>(), len)
+        })
+    }
+
+    /// Stamp the discriminator, version and side count on a freshly allocated bucket account
+    /// Fails if the account is the wrong size for `side_count` or already holds a bucket
+    #[allow(clippy::mut_from_ref)]
+    pub fn init<'a>(account: &'a AccountInfo, program_id: &Pubkey, side_count: u8) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id {
+            return Err(HateFunError::InvalidBucketOwner.into());
+        }
+        if !validate_side_count(side_count) {
+            return Err(HateFunError::InvalidSideCount.into());
+        }
+        if account.data_len() != Self::space(side_count) {
+            return Err(HateFunError::InvalidBucketSize.into());
+        }
+
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let bucket = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if bucket.discriminator != [0u8; 8] {
+            return Err(HateFunError::BucketAlreadyInitialized.into());
+        }
+
+        bucket.discriminator = Self::DISCRIMINATOR;
+        bucket.version = Self::VERSION;
+        bucket.side_count = side_count;
+        Ok(bucket)
+    }
+}
+
+/// Per-depositor contribution receipt for one side of a bucket
+/// Seeded by bucket, side and depositor; created on the first deposit and updated on every later one
+/// Records outlive their bucket, so each one also stores the creation slot of the bucket it counts for
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositRecord {
+    pub discriminator: [u8; 8],      // 8 bytes - Account type tag (DepositRecord::DISCRIMINATOR)
+    pub version: u8,                 // 1 byte - Layout version (DepositRecord::VERSION)
+    pub bump: u8,                    // 1 byte - PDA bump seed
+    pub side: u8,                    // 1 byte - Side the deposits went to
+    pub share_claimed: u8,           // 1 byte - 1 once ClaimShare paid this record
+    pub _padding: [u8; 4],           // 4 bytes - Keeps the following fields 8-byte aligned
+    pub bucket: Pubkey,              // 32 bytes - Bucket the deposits went to
+    pub depositor: Pubkey,           // 32 bytes - Wallet that made the deposits
+    pub total_contributed: u64,      // 8 bytes - Lamports deposited over the record's lifetime
+    pub total_flushed: u64,          // 8 bytes - Contributed lamports known to be flushed into the main bucket
+    pub pending: u64,                // 8 bytes - Contributed lamports not yet known to be flushed
+    pub flush_count: u64,            // 8 bytes - Side's flush count when the record was last updated
+    pub last_deposit_slot: u64,      // 8 bytes - Slot of the most recent deposit
+    pub total_refunded: u64,         // 8 bytes - Lamports returned by RefundPending
+    pub total_withdrawn: u64,        // 8 bytes - Lamports taken back by WithdrawPending
+    pub share_paid: u64,             // 8 bytes - Lamports received from ClaimShare
+    pub bucket_creation_slot: u64,   // 8 bytes - Bucket::creation_slot of the bucket the figures belong to
+}
+
+// The account layout must match the in-memory struct exactly (no hidden padding)
+const _: () = assert!(DepositRecord::SIZE == core::mem::size_of::<DepositRecord>());
+
+impl DepositRecord {
+    /// Size of DepositRecord account in bytes
+    pub const SIZE: usize = 8 + 1 + 1 + 1 + 1 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    /// Account discriminator stored in the first 8 bytes
+    pub const DISCRIMINATOR: [u8; 8] = *b"HFDEPREC";
+
+    /// Current account layout version
+    /// 1: first layout (contribution, pending and flush-count tracking)
+    /// 2: adds the refund, withdrawal and share fields and the bucket's creation slot
+    pub const VERSION: u8 = 2;
+
+    /// Seed prefix for DepositRecord PDA
+    pub const SEED_PREFIX: &'static [u8] = b"deposit";
+
+    /// Validate raw deposit record data: exact size, discriminator and layout version
+    pub fn check_data(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() != Self::SIZE
+            || data[..8] != Self::DISCRIMINATOR
+            || data[8] != Self::VERSION
+        {
+            return Err(HateFunError::InvalidDepositRecord.into());
+        }
+        Ok(())
+    }
+
+    /// Decode a deposit record from raw account data (client-side tooling)
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_data(data)?;
+        // SAFETY: Size is checked above; read_unaligned copes with any buffer alignment
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr().cast::<Self>()) })
+    }
+
+    /// Contributions flushed and still pending as of the side's current flush count
+    /// A flush sweeps the whole escrow, so anything pending before a later flush is gone
+    pub fn flushed_and_pending(&self, side_flush_count: u64) -> Option<(u64, u64)> {
+        settle_deposit_record(self.total_flushed, self.pending, self.flush_count, side_flush_count)
+    }
+
+    /// Whether the figures were recorded for this bucket, not an earlier one at the same address
+    pub fn belongs_to(&self, bucket_key: &Pubkey, bucket: &Bucket) -> bool {
+        &self.bucket == bucket_key && self.bucket_creation_slot == bucket.creation_slot
+    }
+
+    /// Start the record over for `bucket`, dropping any figures left by an earlier bucket
+    /// at the same address; the side's current flush count is the new baseline
+    pub fn reset(&mut self, bucket_key: &Pubkey, bucket: &Bucket, side_flush_count: u64) {
+        self.bucket = *bucket_key;
+        self.bucket_creation_slot = bucket.creation_slot;
+        self.share_claimed = 0;
+        self.total_contributed = 0;
+        self.total_flushed = 0;
+        self.pending = 0;
+        self.flush_count = side_flush_count;
+        self.total_refunded = 0;
+        self.total_withdrawn = 0;
+        self.share_paid = 0;
+    }
+
+    /// Load a validated DepositRecord from account data (mutable)
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_mut<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id {
+            return Err(HateFunError::InvalidDepositRecord.into());
+        }
+        // SAFETY: No other borrow of the record's data is alive here
+        Self::check_data(unsafe { account.borrow_data_unchecked() })?;
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        Ok(unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() })
+    }
+
+    /// Stamp the discriminator and version on a freshly allocated deposit record
+    #[allow(clippy::mut_from_ref)]
+    pub fn init<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::InvalidDepositRecord.into());
+        }
+
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let record = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if record.discriminator != [0u8; 8] {
+            return Err(HateFunError::InvalidDepositRecord.into());
+        }
+
+        record.discriminator = Self::DISCRIMINATOR;
+        record.version = Self::VERSION;
+        Ok(record)
+    }
+}
+
+/// Weighted recipients that share one side's winner cut (a coalition)
+/// Seeded by bucket and side; set by the creator in the bucket's creation slot and immutable afterwards
+/// A config left by an earlier bucket at the same address is overwritten when the new bucket sets one
+#[repr(C)]
+pub struct SideConfig {
+    pub discriminator: [u8; 8],      // 8 bytes - Account type tag (SideConfig::DISCRIMINATOR)
+    pub version: u8,                 // 1 byte - Layout version (SideConfig::VERSION)
+    pub bump: u8,                    // 1 byte - PDA bump seed
+    pub side: u8,                    // 1 byte - Side the recipients stand for
+    pub recipient_count: u8,         // 1 byte - Number of used entries (1-8)
+    pub _padding: [u8; 4],           // 4 bytes - Keeps the following fields 8-byte aligned
+    pub bucket: Pubkey,              // 32 bytes - Bucket the config belongs to
+    pub bucket_creation_slot: u64,   // 8 bytes - Bucket::creation_slot of the bucket the config belongs to
+    pub recipients: [Pubkey; MAX_SIDE_RECIPIENTS as usize],   // 256 bytes - Recipient addresses
+    pub weights_bps: [u16; MAX_SIDE_RECIPIENTS as usize],     // 16 bytes - Recipient weights, summing to 10000
+}
+
+// The account layout must match the in-memory struct exactly (no hidden padding)
+const _: () = assert!(SideConfig::SIZE == core::mem::size_of::<SideConfig>());
+
+impl SideConfig {
+    /// Size of SideConfig account in bytes
+    pub const SIZE: usize = 8 + 1 + 1 + 1 + 1 + 4 + 32 + 8 + 32 * MAX_SIDE_RECIPIENTS as usize + 2 * MAX_SIDE_RECIPIENTS as usize;
+
+    /// Account discriminator stored in the first 8 bytes
+    pub const DISCRIMINATOR: [u8; 8] = *b"HFSIDECF";
+
+    /// Current account layout version
+    /// 1: first layout (side, recipients and weights)
+    /// 2: adds the bucket's creation slot
+    pub const VERSION: u8 = 2;
+
+    /// Seed prefix for SideConfig PDA
+    pub const SEED_PREFIX: &'static [u8] = b"side_config";
+
+    /// Used recipients and their weights
+    pub fn recipients(&self) -> (&[Pubkey], &[u16]) {
+        let count = (self.recipient_count as usize).min(MAX_SIDE_RECIPIENTS as usize);
+        (&self.recipients[..count], &self.weights_bps[..count])
+    }
+
+    /// Load a validated SideConfig from account data (read-only)
+    pub fn load<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::SideConfigNotInitialized.into());
+        }
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let config = unsafe { &*account.borrow_data_unchecked().as_ptr().cast::<Self>() };
+        if config.discriminator != Self::DISCRIMINATOR || config.version != Self::VERSION {
+            return Err(HateFunError::SideConfigNotInitialized.into());
+        }
+        Ok(config)
+    }
+
+    /// Whether the config was set for this bucket, not an earlier one at the same address
+    pub fn belongs_to(&self, bucket_key: &Pubkey, bucket: &Bucket) -> bool {
+        &self.bucket == bucket_key && self.bucket_creation_slot == bucket.creation_slot
+    }
+
+    /// Load a validated SideConfig from account data (mutable)
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_mut<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::SideConfigNotInitialized.into());
+        }
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let config = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if config.discriminator != Self::DISCRIMINATOR || config.version != Self::VERSION {
+            return Err(HateFunError::SideConfigNotInitialized.into());
+        }
+        Ok(config)
+    }
+
+    /// Stamp the discriminator and version on a freshly allocated side config
+    #[allow(clippy::mut_from_ref)]
+    pub fn init<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::SideConfigNotInitialized.into());
+        }
+
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let config = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if config.discriminator != [0u8; 8] {
+            return Err(HateFunError::SideAlreadyConfigured.into());
+        }
+
+        config.discriminator = Self::DISCRIMINATOR;
+        config.version = Self::VERSION;
+        Ok(config)
+    }
+}
+
+/// Frozen split of a finalized bucket's pot; each payee withdraws their own entries
+/// Seeded by bucket and the bucket's creation slot, so a bucket re-created at the same address
+/// gets its own settlement; entry 0 is the creator, entry 1 the finalizer (claimer fee),
+/// entry 2 the payer's rent refund, then the winner or the winning coalition's recipients
+/// Only fully withdrawn settlements close: an entry its payee can never collect keeps it open
+#[repr(C)]
+pub struct Settlement {
+    pub discriminator: [u8; 8],      // 8 bytes - Account type tag (Settlement::DISCRIMINATOR)
+    pub version: u8,                 // 1 byte - Layout version (Settlement::VERSION)
+    pub bump: u8,                    // 1 byte - PDA bump seed
+    pub payee_count: u8,             // 1 byte - Number of used entries
+    pub _padding: [u8; 5],           // 5 bytes - Keeps the following fields 8-byte aligned
+    pub bucket: Pubkey,              // 32 bytes - Bucket the settlement belongs to
+    pub payees: [Pubkey; Settlement::MAX_PAYEES],   // 352 bytes - Who may withdraw each entry
+    pub amounts: [u64; Settlement::MAX_PAYEES],     // 88 bytes - Lamports owed per entry
+    pub withdrawn: [u8; Settlement::MAX_PAYEES],    // 11 bytes - 1 once the entry was withdrawn
+    pub _reserved: [u8; 5],          // 5 bytes - Pads the struct to a multiple of 8
+}
+
+// The account layout must match the in-memory struct exactly (no hidden padding)
+const _: () = assert!(Settlement::SIZE == core::mem::size_of::<Settlement>());
+
+impl Settlement {
+    /// Creator, claimer, the payer's rent refund and up to MAX_SIDE_RECIPIENTS winners
+    pub const MAX_PAYEES: usize = 3 + MAX_SIDE_RECIPIENTS as usize;
+
+    /// Size of Settlement account in bytes
+    pub const SIZE: usize = 8 + 1 + 1 + 1 + 5 + 32 + 32 * Self::MAX_PAYEES + 8 * Self::MAX_PAYEES + Self::MAX_PAYEES + 5;
+
+    /// Account discriminator stored in the first 8 bytes
+    pub const DISCRIMINATOR: [u8; 8] = *b"HFSETTLE";
+
+    /// Current account layout version
+    /// 1: first layout (creator, claimer and winner entries)
+    /// 2: adds the payer's rent refund entry
+    pub const VERSION: u8 = 2;
+
+    /// Seed prefix for Settlement PDA
+    pub const SEED_PREFIX: &'static [u8] = b"settlement";
+
+    /// Entry of the finalizer, who paid the settlement's rent and gets it back on close
+    pub const CLAIMER_INDEX: usize = 1;
+
+    /// Append an entry owed to `payee`; an empty entry is born withdrawn
+    pub fn push(&mut self, payee: &Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let index = self.payee_count as usize;
+        if index >= Self::MAX_PAYEES {
+            return Err(HateFunError::InvalidSettlement.into());
+        }
+        self.payees[index] = *payee;
+        self.amounts[index] = amount;
+        self.withdrawn[index] = (amount == 0) as u8;
+        self.payee_count += 1;
+        Ok(())
+    }
+
+    /// Whether every entry has been paid out
+    pub fn is_fully_withdrawn(&self) -> bool {
+        let payee_count = (self.payee_count as usize).min(Self::MAX_PAYEES);
+        self.withdrawn[..payee_count].iter().all(|&withdrawn| withdrawn != 0)
+    }
+
+    /// Load a validated Settlement from account data (mutable)
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_mut<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::InvalidSettlement.into());
+        }
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let settlement = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if settlement.discriminator != Self::DISCRIMINATOR || settlement.version != Self::VERSION {
+            return Err(HateFunError::InvalidSettlement.into());
+        }
+        Ok(settlement)
+    }
+
+    /// Stamp the discriminator and version on a freshly allocated settlement
+    #[allow(clippy::mut_from_ref)]
+    pub fn init<'a>(account: &'a AccountInfo, program_id: &Pubkey) -> Result<&'a mut Self, ProgramError> {
+        if account.owner() != program_id || account.data_len() != Self::SIZE {
+            return Err(HateFunError::InvalidSettlement.into());
+        }
+
+        // SAFETY: Size is checked above and the runtime aligns account data to 8 bytes
+        let settlement = unsafe { &mut *account.borrow_mut_data_unchecked().as_mut_ptr().cast::<Self>() };
+        if settlement.discriminator != [0u8; 8] {
+            return Err(HateFunError::InvalidSettlement.into());
+        }
+
+        settlement.discriminator = Self::DISCRIMINATOR;
+        settlement.version = Self::VERSION;
+        Ok(settlement)
     }
 }
 
@@ -53,6 +745,7 @@ impl Bucket {
 pub mod pda {
     use super::*;
     use pinocchio::pubkey::find_program_address;
+    use crate::verification::MAX_SIDES;
 
     /// Derive bucket PDA address
     pub fn derive_bucket_address(
@@ -98,4 +791,253 @@ pub mod pda {
             program_id,
         )
     }
+
+    /// Derive the escrow PDA address for a side index
+    /// Sides 0 and 1 keep their escrow_a / escrow_b seeds; sides 2 and up use
+    /// [ESCROW_SEED_PREFIX, bucket, side]
+    pub fn derive_escrow_address(
+        bucket: &Pubkey,
+        side: u8,
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), ProgramError> {
+        match side {
+            Bucket::SIDE_A => Ok(derive_escrow_a_address(bucket, program_id)),
+            Bucket::SIDE_B => Ok(derive_escrow_b_address(bucket, program_id)),
+            _ if side < MAX_SIDES => Ok(find_program_address(
+                &[Bucket::ESCROW_SEED_PREFIX, bucket.as_ref(), &[side]],
+                program_id,
+            )),
+            _ => Err(HateFunError::InvalidSide.into()),
+        }
+    }
+
+    /// Derive a depositor's DepositRecord PDA for one side of a bucket
+    pub fn derive_deposit_record_address(
+        bucket: &Pubkey,
+        side: u8,
+        depositor: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        find_program_address(
+            &[DepositRecord::SEED_PREFIX, bucket.as_ref(), &[side], depositor.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Derive the SideConfig PDA for one side of a bucket
+    pub fn derive_side_config_address(
+        bucket: &Pubkey,
+        side: u8,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        find_program_address(
+            &[SideConfig::SEED_PREFIX, bucket.as_ref(), &[side]],
+            program_id,
+        )
+    }
+
+    /// Derive the Settlement PDA of one bucket instance
+    pub fn derive_settlement_address(
+        bucket: &Pubkey,
+        bucket_creation_slot: u64,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        find_program_address(
+            &[Settlement::SEED_PREFIX, bucket.as_ref(), &bucket_creation_slot.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket_data() -> [u8; Bucket::SIZE] {
+        let mut data = [0u8; Bucket::SIZE];
+        data[..8].copy_from_slice(&Bucket::DISCRIMINATOR);
+        data[8] = Bucket::VERSION;
+        data[core::mem::offset_of!(Bucket, side_count)] = 2;
+        data
+    }
+
+    #[test]
+    fn test_bucket_layout() {
+        assert_eq!(Bucket::SIZE, core::mem::size_of::<Bucket>());
+        assert_eq!(Bucket::SIZE % 8, 0);
+        assert_eq!(core::mem::offset_of!(Bucket, address_a), 16);
+        assert_eq!(core::mem::offset_of!(Bucket, seed), 112);
+        assert_eq!(core::mem::offset_of!(Bucket, payer), 144);
+    }
+
+    #[test]
+    fn test_side_config_layout() {
+        assert_eq!(SideConfig::SIZE, 328);
+        assert_eq!(SideConfig::SIZE % 8, 0);
+        assert_eq!(core::mem::offset_of!(SideConfig, bucket), 16);
+    }
+
+    #[test]
+    fn test_settlement_layout() {
+        assert_eq!(Settlement::SIZE, 504);
+        assert_eq!(Settlement::SIZE % 8, 0);
+        assert_eq!(core::mem::offset_of!(Settlement, payees), 48);
+    }
+
+    #[test]
+    fn test_settlement_fully_withdrawn() {
+        // SAFETY: Settlement is plain integers and byte arrays, so all-zero is a valid value
+        let mut settlement: Settlement = unsafe { core::mem::zeroed() };
+        settlement.push(&[1u8; 32], 100).unwrap();
+        settlement.push(&[2u8; 32], 0).unwrap();
+        assert_eq!(settlement.withdrawn[1], 1, "Empty entries need no withdrawal");
+        assert!(!settlement.is_fully_withdrawn());
+
+        settlement.withdrawn[0] = 1;
+        assert!(settlement.is_fully_withdrawn());
+
+        for _ in 2..Settlement::MAX_PAYEES {
+            settlement.push(&[3u8; 32], 1).unwrap();
+        }
+        assert_eq!(settlement.push(&[4u8; 32], 1), Err(HateFunError::InvalidSettlement.into()));
+    }
+
+    #[test]
+    fn test_check_data_accepts_valid_bucket() {
+        assert_eq!(Bucket::check_data(&bucket_data()), Ok(()));
+    }
+
+    #[test]
+    fn test_check_data_rejects_wrong_size() {
+        let data = bucket_data();
+        assert_eq!(
+            Bucket::check_data(&data[..Bucket::SIZE - 1]),
+            Err(HateFunError::InvalidBucketSize.into())
+        );
+        assert_eq!(Bucket::check_data(&[]), Err(HateFunError::InvalidBucketSize.into()));
+    }
+
+    #[test]
+    fn test_deposit_record_decode() {
+        let mut data = [0u8; DepositRecord::SIZE];
+        data[..8].copy_from_slice(&DepositRecord::DISCRIMINATOR);
+        data[8] = DepositRecord::VERSION;
+        data[10] = 2;
+        data[core::mem::offset_of!(DepositRecord, total_contributed)..][..8]
+            .copy_from_slice(&3_000_000_000u64.to_le_bytes());
+
+        let record = DepositRecord::decode(&data).unwrap();
+        assert_eq!(record.side, 2);
+        assert_eq!(record.total_contributed, 3_000_000_000);
+
+        assert_eq!(
+            DepositRecord::decode(&data[..DepositRecord::SIZE - 1]),
+            Err(HateFunError::InvalidDepositRecord.into())
+        );
+        assert_eq!(
+            DepositRecord::decode(&bucket_data()),
+            Err(HateFunError::InvalidDepositRecord.into())
+        );
+    }
+
+    #[test]
+    fn test_deposit_record_reset_for_recreated_bucket() {
+        let bucket_key: Pubkey = [5u8; 32];
+        let mut old_bucket = Bucket::decode(&bucket_data()).unwrap();
+        old_bucket.creation_slot = 100;
+        let mut new_bucket = Bucket::decode(&bucket_data()).unwrap();
+        new_bucket.creation_slot = 250;
+
+        let mut data = [0u8; DepositRecord::SIZE];
+        data[..8].copy_from_slice(&DepositRecord::DISCRIMINATOR);
+        data[8] = DepositRecord::VERSION;
+        let mut record = DepositRecord::decode(&data).unwrap();
+
+        // A fresh record belongs to no bucket until the first deposit resets it
+        assert!(!record.belongs_to(&bucket_key, &old_bucket));
+        record.reset(&bucket_key, &old_bucket, 0);
+        assert!(record.belongs_to(&bucket_key, &old_bucket));
+
+        // Figures left behind by the earlier bucket at the same address
+        record.total_contributed = 2_000;
+        record.pending = 2_000;
+        record.total_flushed = 500;
+        record.share_claimed = 1;
+        record.flush_count = 4;
+        assert!(!record.belongs_to(&bucket_key, &new_bucket));
+        assert!(!record.belongs_to(&[6u8; 32], &old_bucket));
+
+        record.reset(&bucket_key, &new_bucket, 0);
+        assert!(record.belongs_to(&bucket_key, &new_bucket));
+        assert!(!record.belongs_to(&bucket_key, &old_bucket));
+        assert_eq!(record.total_contributed, 0);
+        assert_eq!(record.pending, 0);
+        assert_eq!(record.total_flushed, 0);
+        assert_eq!(record.share_claimed, 0);
+        assert_eq!(record.flush_count, 0);
+    }
+
+    #[test]
+    fn test_is_authorized_by_creator() {
+        use solana_program::pubkey::Pubkey as HostPubkey;
+
+        let program_id = HostPubkey::new_unique();
+        let create_address = |seeds: &[&[u8]]| {
+            HostPubkey::create_program_address(seeds, &program_id).ok().map(|address| address.to_bytes())
+        };
+
+        let mut bucket = Bucket::decode(&bucket_data()).unwrap();
+        bucket.creator_address = [7u8; 32];
+        bucket.seed = [9u8; 32];
+        let (bucket_pda, bump) = HostPubkey::find_program_address(
+            &[Bucket::SEED_PREFIX, &bucket.creator_address, &bucket.seed],
+            &program_id,
+        );
+        bucket.bump = bump;
+        let bucket_key = bucket_pda.to_bytes();
+        assert!(bucket.is_authorized_by_creator_with(&bucket_key, create_address));
+
+        // Another address, or a bucket claiming another creator, does not match
+        assert!(!bucket.is_authorized_by_creator_with(&[1u8; 32], create_address));
+        bucket.creator_address = [8u8; 32];
+        assert!(!bucket.is_authorized_by_creator_with(&bucket_key, create_address));
+    }
+
+    #[test]
+    fn test_check_data_sizes_by_side_count() {
+        // Three sides: one trailing address after the header
+        let mut data = [0u8; Bucket::SIZE + 32];
+        data[..Bucket::SIZE].copy_from_slice(&bucket_data());
+        data[core::mem::offset_of!(Bucket, side_count)] = 3;
+        assert_eq!(Bucket::space(3), Bucket::SIZE + 32);
+        assert_eq!(Bucket::check_data(&data), Ok(()));
+
+        // Header claims three sides but the trailing address is missing
+        assert_eq!(
+            Bucket::check_data(&data[..Bucket::SIZE]),
+            Err(HateFunError::InvalidBucketSize.into())
+        );
+
+        // Side counts outside 2..=16 are rejected
+        data[core::mem::offset_of!(Bucket, side_count)] = 1;
+        assert_eq!(Bucket::check_data(&data), Err(HateFunError::InvalidSideCount.into()));
+        data[core::mem::offset_of!(Bucket, side_count)] = 17;
+        assert_eq!(Bucket::check_data(&data), Err(HateFunError::InvalidSideCount.into()));
+    }
+
+    #[test]
+    fn test_check_data_rejects_wrong_discriminator() {
+        // A zeroed (closed or uninitialized) account is not a bucket
+        assert_eq!(
+            Bucket::check_data(&[0u8; Bucket::SIZE]),
+            Err(HateFunError::InvalidBucketDiscriminator.into())
+        );
+    }
+
+    #[test]
+    fn test_check_data_rejects_unknown_version() {
+        let mut data = bucket_data();
+        data[8] = Bucket::VERSION + 1;
+        assert_eq!(Bucket::check_data(&data), Err(HateFunError::UnsupportedBucketVersion.into()));
+    }
 }
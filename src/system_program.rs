@@ -16,7 +16,9 @@ pub const ID: Pubkey = [
 
 /// System Program instruction discriminators
 const CREATE_ACCOUNT: u32 = 0;
+const ASSIGN: u32 = 1;
 const TRANSFER: u32 = 2;
+const ALLOCATE: u32 = 8;
 
 /// Create a new account
 pub fn create_account<'a>(
@@ -54,6 +56,73 @@ pub fn create_account<'a>(
     invoke_signed(&instruction, &account_infos, &signers)
 }
 
+/// Create a PDA account, even if its address was already funded
+/// create_account fails on any address holding lamports, so anyone could block a predictable
+/// PDA by sending it dust. A funded address is topped up to `lamports` instead, then allocated
+/// and assigned; an address that already holds data or belongs to another program still fails
+pub fn initialize_pda<'a>(
+    from: &'a AccountInfo,
+    to: &'a AccountInfo,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+    seeds: &[Seed],
+) -> ProgramResult {
+    let current_lamports = to.lamports();
+    if current_lamports == 0 {
+        return create_account(from, to, lamports, space, owner, seeds);
+    }
+
+    if current_lamports < lamports {
+        transfer(from, to, lamports - current_lamports)?;
+    }
+
+    allocate(to, space, seeds)?;
+    assign(to, owner, seeds)
+}
+
+/// Allocate space for a PDA account
+pub fn allocate(account: &AccountInfo, space: u64, seeds: &[Seed]) -> ProgramResult {
+    let mut instruction_data = [0u8; 12];
+    // discriminator (4 bytes)
+    instruction_data[0..4].copy_from_slice(&ALLOCATE.to_le_bytes());
+    // space (8 bytes)
+    instruction_data[4..12].copy_from_slice(&space.to_le_bytes());
+
+    let accounts = [AccountMeta::writable_signer(account.key())];
+
+    let instruction = Instruction {
+        program_id: &ID,
+        data: &instruction_data,
+        accounts: &accounts,
+    };
+
+    let signers = [Signer::from(seeds)];
+
+    invoke_signed(&instruction, &[account], &signers)
+}
+
+/// Assign a PDA account to a program
+pub fn assign(account: &AccountInfo, owner: &Pubkey, seeds: &[Seed]) -> ProgramResult {
+    let mut instruction_data = [0u8; 36];
+    // discriminator (4 bytes)
+    instruction_data[0..4].copy_from_slice(&ASSIGN.to_le_bytes());
+    // owner (32 bytes)
+    instruction_data[4..36].copy_from_slice(owner.as_ref());
+
+    let accounts = [AccountMeta::writable_signer(account.key())];
+
+    let instruction = Instruction {
+        program_id: &ID,
+        data: &instruction_data,
+        accounts: &accounts,
+    };
+
+    let signers = [Signer::from(seeds)];
+
+    invoke_signed(&instruction, &[account], &signers)
+}
+
 /// Transfer lamports
 pub fn transfer<'a>(
     from: &'a AccountInfo,
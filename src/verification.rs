@@ -2,6 +2,60 @@
 // This module contains pure arithmetic functions extracted from the main program
 // along with Kani proof harnesses to verify their correctness
 
+use pinocchio::pubkey::Pubkey;
+
+use crate::{
+    error::HateFunError,
+    state::{BucketAction, BucketStatus, ClaimDelayUnit},
+};
+
+/// Program-wide floors for the per-bucket minimums
+/// Deposits below 1,000 lamports are dust; initial swaps below 0.0001 SOL make flips trivially cheap
+pub const MIN_DEPOSIT_FLOOR: u64 = 1_000;
+pub const MIN_INITIAL_SWAP_FLOOR: u64 = 100_000;
+
+/// Claim delay bounds per unit
+/// Slots and seconds allow short campaigns (~1 minute), epochs allow long ones (~60 days)
+pub const MIN_CLAIM_DELAY_EPOCHS: u64 = 1;
+pub const MAX_CLAIM_DELAY_EPOCHS: u64 = 30;
+pub const MIN_CLAIM_DELAY_SLOTS: u64 = 150;
+pub const MAX_CLAIM_DELAY_SLOTS: u64 = 12_960_000;
+pub const MIN_CLAIM_DELAY_SECONDS: u64 = 60;
+pub const MAX_CLAIM_DELAY_SECONDS: u64 = 5_184_000;
+
+/// Number of competing sides a bucket can have
+pub const MIN_SIDES: u8 = 2;
+pub const MAX_SIDES: u8 = 16;
+
+/// Number of weighted recipients one side's winner cut can be split among
+pub const MAX_SIDE_RECIPIENTS: u8 = 8;
+
+/// Caller-supplied preconditions on bucket state, like AMM slippage protection
+/// Each field is optional; None skips that check
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapGuard {
+    pub expected_last_swap: Option<u64>,
+    pub expected_current_target: Option<Pubkey>,
+    pub max_lamports: Option<u64>,
+}
+
+impl SwapGuard {
+    /// Verify the bucket is still in the state the caller signed against
+    /// `lamports` is what the instruction takes from the caller (0 for a plain flush)
+    pub fn check(&self, last_swap: u64, current_target: &Pubkey, lamports: u64) -> Result<(), HateFunError> {
+        if self.expected_last_swap.is_some_and(|expected| expected != last_swap) {
+            return Err(HateFunError::LastSwapMismatch);
+        }
+        if self.expected_current_target.is_some_and(|expected| &expected != current_target) {
+            return Err(HateFunError::CurrentTargetMismatch);
+        }
+        if self.max_lamports.is_some_and(|max| lamports > max) {
+            return Err(HateFunError::MaxLamportsExceeded);
+        }
+        Ok(())
+    }
+}
+
 /// Calculate the threshold required to flip an escrow
 /// Returns None if multiplication or division would overflow
 pub fn calculate_flush_threshold(last_swap: u64, min_increase_bps: u16) -> Option<u64> {
@@ -10,6 +64,167 @@ pub fn calculate_flush_threshold(last_swap: u64, min_increase_bps: u16) -> Optio
         .checked_div(10000)
 }
 
+/// Calculate how much of an escrow balance can be flushed into the main bucket
+/// The rent-exempt reserve always stays behind so the escrow PDA is never garbage-collected
+pub fn calculate_flushable_balance(escrow_balance: u64, rent_exempt_reserve: u64) -> u64 {
+    escrow_balance.saturating_sub(rent_exempt_reserve)
+}
+
+/// Calculate the deposit DepositAndFlush makes when no amount is given
+/// Tops the escrow's flushable balance up to the threshold, never below the bucket's
+/// minimum deposit; returns 0 when the escrow already meets the threshold
+pub fn calculate_flip_deposit(flushable: u64, threshold: u64, min_deposit: u64) -> u64 {
+    if flushable >= threshold {
+        return 0;
+    }
+    (threshold - flushable).max(min_deposit)
+}
+
+/// Determine the new current_target after flushing one side's escrow
+/// Control always moves to the flushed side; returns None if that side already
+/// controls the bucket (flushing it would not flip anything)
+pub fn resolve_flush_target(flushed_side: &Pubkey, current_target: &Pubkey) -> Option<Pubkey> {
+    if flushed_side == current_target {
+        return None;
+    }
+    Some(*flushed_side)
+}
+
+/// Record a successful flip of `flushed` lamports into the bucket counters
+/// Returns the updated (flip_count, total_flushed) or None on overflow
+pub fn record_flip(flip_count: u64, total_flushed: u64, flushed: u64) -> Option<(u64, u64)> {
+    Some((flip_count.checked_add(1)?, total_flushed.checked_add(flushed)?))
+}
+
+/// Record a reinforcement of `reinforced` lamports by the controlling side
+/// Counts toward total_flushed (so the bucket can no longer be closed) but not toward flip_count
+/// Returns the updated (reinforce_count, total_flushed) or None on overflow
+pub fn record_reinforce(reinforce_count: u64, total_flushed: u64, reinforced: u64) -> Option<(u64, u64)> {
+    Some((reinforce_count.checked_add(1)?, total_flushed.checked_add(reinforced)?))
+}
+
+/// Settle a deposit record against its side's current flush count
+/// A flush sweeps the side's whole escrow, so if the side was flushed since the record's
+/// last update, everything pending then has been flushed
+/// Returns the updated (total_flushed, pending) or None on overflow
+pub fn settle_deposit_record(
+    total_flushed: u64,
+    pending: u64,
+    record_flush_count: u64,
+    side_flush_count: u64,
+) -> Option<(u64, u64)> {
+    if side_flush_count == record_flush_count {
+        return Some((total_flushed, pending));
+    }
+    Some((total_flushed.checked_add(pending)?, 0))
+}
+
+/// Add a deposit of `amount` lamports to a settled deposit record
+/// Returns the updated (total_contributed, pending) or None on overflow
+pub fn record_deposit(total_contributed: u64, pending: u64, amount: u64) -> Option<(u64, u64)> {
+    Some((total_contributed.checked_add(amount)?, pending.checked_add(amount)?))
+}
+
+/// Calculate a depositor's pro-rata share of a pool that depositors draw from one at a time
+/// Refunds: `pool` is the escrow balance above its reserve and `total` the side's pending deposits
+/// ClaimShare: `pool` is the winner's cut still held and `total` the unclaimed winning contributions
+/// Each payout shrinks both pool and total, so the last depositor also takes the rounding dust
+pub fn calculate_pro_rata_share(pool: u64, amount: u64, total: u64) -> u64 {
+    if total == 0 || amount >= total {
+        return pool;
+    }
+    // amount < total, so the result is below pool and fits in u64
+    ((pool as u128 * amount as u128) / total as u128) as u64
+}
+
+/// Calculate how much of a depositor's pending deposit can be withdrawn from the escrow
+/// Bounded by the depositor's pending receipt and by the escrow balance above its reserve
+pub fn calculate_withdrawable(pending: u64, flushable: u64) -> u64 {
+    pending.min(flushable)
+}
+
+/// Validate a side's recipient weights: 1-8 entries, none zero, summing to exactly 100%
+pub fn validate_side_weights(weights_bps: &[u16]) -> bool {
+    !weights_bps.is_empty()
+        && weights_bps.len() <= MAX_SIDE_RECIPIENTS as usize
+        && weights_bps.iter().all(|&weight| weight > 0)
+        && weights_bps.iter().map(|&weight| weight as u32).sum::<u32>() == 10_000
+}
+
+/// Split the winner's cut among a side's recipients by weight
+/// Each recipient gets amount × weight / 10000 rounded down; the first recipient also takes the dust
+/// Returns one cut per weight (unused entries stay zero) or None if the weights are invalid
+pub fn calculate_weighted_split(amount: u64, weights_bps: &[u16]) -> Option<[u64; MAX_SIDE_RECIPIENTS as usize]> {
+    if !validate_side_weights(weights_bps) {
+        return None;
+    }
+
+    let mut cuts = [0u64; MAX_SIDE_RECIPIENTS as usize];
+    let mut distributed: u64 = 0;
+    for (cut, &weight) in cuts.iter_mut().zip(weights_bps) {
+        // Use u128 for intermediate calculations to prevent overflow
+        *cut = ((amount as u128 * weight as u128) / 10_000) as u64;
+        distributed = distributed.checked_add(*cut)?;
+    }
+
+    // Weights sum to 10000, so the rounded-down cuts never exceed amount
+    cuts[0] = cuts[0].checked_add(amount.checked_sub(distributed)?)?;
+    Some(cuts)
+}
+
+/// Check whether the creator may still close the bucket
+/// Only allowed while nothing has ever been flipped or flushed into the pot
+pub fn can_close_bucket(flip_count: u64, total_flushed: u64) -> bool {
+    flip_count == 0 && total_flushed == 0
+}
+
+/// Resolve the status a bucket is effectively in right now
+/// An open bucket becomes claimable once the claim window has elapsed since the last flip
+pub fn effective_status(stored: BucketStatus, claim_window_elapsed: bool) -> BucketStatus {
+    if stored == BucketStatus::Open && claim_window_elapsed {
+        return BucketStatus::Claimable;
+    }
+    stored
+}
+
+/// Lifecycle transition for applying `action` to a bucket in `status`
+/// Returns the resulting status, or the error explaining why the action is invalid in this phase
+pub fn next_status(status: BucketStatus, action: BucketAction) -> Result<BucketStatus, HateFunError> {
+    match (status, action) {
+        // Refunds of pending deposits and proportional shares are the only actions left after settlement,
+        // then the drained bucket is closed
+        (BucketStatus::Settled, BucketAction::Refund | BucketAction::ClaimShare) => Ok(BucketStatus::Settled),
+        (BucketStatus::Settled, BucketAction::CloseSettled) => Ok(BucketStatus::Closed),
+        (BucketStatus::Settled, _) => Err(HateFunError::BucketSettled),
+        (BucketStatus::Closed, _) => Err(HateFunError::BucketClosed),
+        (_, BucketAction::Refund | BucketAction::ClaimShare | BucketAction::CloseSettled) => Err(HateFunError::BucketNotSettled),
+        (BucketStatus::Open, BucketAction::Claim) => Err(HateFunError::ClaimTooEarly),
+        // Side configs are terms of an untouched bucket; a claimable one has long been played
+        (BucketStatus::Open, BucketAction::ConfigureSides) => Ok(BucketStatus::Open),
+        (_, BucketAction::ConfigureSides) => Err(HateFunError::SideConfigLocked),
+        // Reinforcing raises the bar without flipping, so the claim window keeps running
+        // Withdrawing a pending deposit takes nothing from the pot
+        (_, BucketAction::Deposit | BucketAction::Reinforce | BucketAction::Withdraw) => Ok(status),
+        // A flip restarts the claim window
+        (_, BucketAction::Flush) => Ok(BucketStatus::Open),
+        (_, BucketAction::Claim) => Ok(BucketStatus::Settled),
+        (_, BucketAction::Close) => Ok(BucketStatus::Closed),
+    }
+}
+
+/// Check that closing a live bucket's deposit record forfeits nothing its depositor could still
+/// collect: no pending deposit left to withdraw or refund (unless the claim swept it) and no
+/// proportional share left to claim
+pub fn can_close_deposit_record(pending: u64, pending_swept: bool, share_owed: bool) -> bool {
+    (pending == 0 || pending_swept) && !share_owed
+}
+
+/// Check that a settled bucket owes nothing more: every side's pending deposits were
+/// refunded and every proportional share was claimed
+pub fn is_settlement_drained(side_pending: &[u64], share_pool: u64, unclaimed_contributions: u64) -> bool {
+    share_pool == 0 && unclaimed_contributions == 0 && side_pending.iter().all(|&pending| pending == 0)
+}
+
 /// Calculate fee amounts and winner payout
 /// Returns (creator_cut, claimer_cut, winner_cut) or None on overflow
 pub fn calculate_payout_distribution(
@@ -57,7 +272,77 @@ pub fn validate_fees(creator_fee_bps: u16, claimer_fee_bps: u16) -> bool {
 
 /// Validate minimum increase bounds
 pub fn validate_min_increase(min_increase_bps: u16) -> bool {
-    min_increase_bps >= 100 && min_increase_bps <= 5000
+    (100..=5000).contains(&min_increase_bps)
+}
+
+/// Validate a bucket's configured minimum deposit
+pub fn validate_min_deposit(min_deposit: u64) -> bool {
+    min_deposit >= MIN_DEPOSIT_FLOOR
+}
+
+/// Validate a bucket's configured minimum initial swap
+pub fn validate_min_initial_swap(min_initial_swap: u64) -> bool {
+    min_initial_swap >= MIN_INITIAL_SWAP_FLOOR
+}
+
+/// Check the initial last_swap against the bucket's minimum initial swap
+pub fn meets_min_initial_swap(initial_last_swap: u64, min_initial_swap: u64) -> bool {
+    initial_last_swap >= min_initial_swap
+}
+
+/// Check a deposit amount against the bucket's minimum deposit
+pub fn meets_min_deposit(amount: u64, min_deposit: u64) -> bool {
+    amount >= min_deposit
+}
+
+/// Validate the number of competing sides
+pub fn validate_side_count(side_count: u8) -> bool {
+    (MIN_SIDES..=MAX_SIDES).contains(&side_count)
+}
+
+/// Check that no two sides share an address
+/// Control is tracked by address, so duplicates would make two sides indistinguishable
+pub fn has_unique_addresses(addresses: &[Pubkey]) -> bool {
+    addresses
+        .iter()
+        .enumerate()
+        .all(|(i, address)| !addresses[i + 1..].contains(address))
+}
+
+/// Pick a random starting side from a SlotHashes entry
+/// The first 8 bytes of the hash, read little-endian, modulo the side count
+/// Returns None for an invalid side count
+pub fn select_random_side(slot_hash: &[u8; 32], side_count: u8) -> Option<u8> {
+    if !validate_side_count(side_count) {
+        return None;
+    }
+    let mut entropy = [0u8; 8];
+    entropy.copy_from_slice(&slot_hash[..8]);
+    Some((u64::from_le_bytes(entropy) % side_count as u64) as u8)
+}
+
+/// Validate claim delay bounds for the chosen unit
+pub fn validate_claim_delay(unit: ClaimDelayUnit, claim_delay: u64) -> bool {
+    match unit {
+        ClaimDelayUnit::Epochs => (MIN_CLAIM_DELAY_EPOCHS..=MAX_CLAIM_DELAY_EPOCHS).contains(&claim_delay),
+        ClaimDelayUnit::Slots => (MIN_CLAIM_DELAY_SLOTS..=MAX_CLAIM_DELAY_SLOTS).contains(&claim_delay),
+        ClaimDelayUnit::Seconds => (MIN_CLAIM_DELAY_SECONDS..=MAX_CLAIM_DELAY_SECONDS).contains(&claim_delay),
+    }
+}
+
+/// Calculate the earliest point (epoch, slot or Unix timestamp) at which the payout can be claimed
+/// Returns None on overflow
+pub fn calculate_claim_deadline(last_flip: u64, claim_delay: u64) -> Option<u64> {
+    last_flip.checked_add(claim_delay)
+}
+
+/// Check whether the claim window has elapsed
+/// An overflowing deadline is never reached
+pub fn is_claim_window_elapsed(now: u64, last_flip: u64, claim_delay: u64) -> bool {
+    match calculate_claim_deadline(last_flip, claim_delay) {
+        Some(deadline) => now >= deadline,
+        None => false,
+    }
 }
 
 /// Check if escrow balance is considered "empty" (at or below dust threshold)
@@ -267,6 +552,488 @@ mod proofs {
             assert!(winner_cut <= total);
         }
     }
+
+    // Proof 10: Flush hands control to the flushed side, never to the opponent
+    #[kani::proof]
+    fn verify_flush_target_is_flushed_side() {
+        let address_a: Pubkey = kani::any();
+        let address_b: Pubkey = kani::any();
+        let current_is_a: bool = kani::any();
+        let flushed_is_a: bool = kani::any();
+
+        kani::assume(address_a != address_b);
+
+        let current_target = if current_is_a { address_a } else { address_b };
+        let flushed_side = if flushed_is_a { address_a } else { address_b };
+
+        let result = resolve_flush_target(&flushed_side, &current_target);
+
+        // Property: Flushing the controlling side is rejected
+        if flushed_is_a == current_is_a {
+            assert!(result.is_none());
+        }
+
+        // Property: Flushing the other side gives control to the flushed side
+        if flushed_is_a != current_is_a {
+            assert_eq!(result, Some(flushed_side));
+        }
+    }
+
+    // Proof 11: Flushing never dips into the escrow's rent-exempt reserve
+    #[kani::proof]
+    fn verify_flush_preserves_rent_reserve() {
+        let escrow_balance: u64 = kani::any();
+        let rent_exempt_reserve: u64 = kani::any();
+
+        let flushable = calculate_flushable_balance(escrow_balance, rent_exempt_reserve);
+
+        // Property: Never flush more than the escrow holds
+        assert!(flushable <= escrow_balance);
+
+        // Property: What stays behind covers the reserve (or everything if underfunded)
+        let remaining = escrow_balance - flushable;
+        assert!(remaining == rent_exempt_reserve || (escrow_balance < rent_exempt_reserve && flushable == 0));
+
+        // Property: Everything above the reserve is flushable
+        if escrow_balance >= rent_exempt_reserve {
+            assert_eq!(flushable + rent_exempt_reserve, escrow_balance);
+        }
+    }
+
+    // Proof 12: Close is impossible after any flip, including one in the creation epoch
+    #[kani::proof]
+    fn verify_close_impossible_after_flip() {
+        let flip_count: u64 = kani::any();
+        let total_flushed: u64 = kani::any();
+        let flushed: u64 = kani::any();
+
+        // Property: Any successfully recorded flip permanently blocks closing
+        if let Some((new_flip_count, new_total_flushed)) = record_flip(flip_count, total_flushed, flushed) {
+            assert!(new_flip_count > flip_count);
+            assert!(!can_close_bucket(new_flip_count, new_total_flushed));
+        }
+
+        // Property: Close is only allowed with both counters at zero
+        if can_close_bucket(flip_count, total_flushed) {
+            assert!(flip_count == 0 && total_flushed == 0);
+        }
+    }
+
+    // Proof 13: Settled and closed buckets are terminal
+    #[kani::proof]
+    fn verify_terminal_statuses() {
+        let stored: u8 = kani::any();
+        let action_index: u8 = kani::any();
+        let claim_window_elapsed: bool = kani::any();
+
+        kani::assume(stored <= 3);
+        kani::assume(action_index <= 9);
+
+        let stored = BucketStatus::try_from(stored).unwrap();
+        let action = match action_index {
+            0 => BucketAction::Deposit,
+            1 => BucketAction::Flush,
+            2 => BucketAction::Reinforce,
+            3 => BucketAction::Claim,
+            4 => BucketAction::Close,
+            5 => BucketAction::Refund,
+            6 => BucketAction::Withdraw,
+            7 => BucketAction::ClaimShare,
+            8 => BucketAction::CloseSettled,
+            _ => BucketAction::ConfigureSides,
+        };
+
+        let status = effective_status(stored, claim_window_elapsed);
+        let result = next_status(status, action);
+
+        // Property: Nothing but refunds, shares and the final close is allowed after settlement,
+        // nothing at all after closing
+        if stored == BucketStatus::Settled {
+            if action == BucketAction::Refund || action == BucketAction::ClaimShare {
+                assert_eq!(result, Ok(BucketStatus::Settled));
+            } else if action == BucketAction::CloseSettled {
+                assert_eq!(result, Ok(BucketStatus::Closed));
+            } else {
+                assert_eq!(result, Err(HateFunError::BucketSettled));
+            }
+        }
+        if stored == BucketStatus::Closed {
+            assert_eq!(result, Err(HateFunError::BucketClosed));
+        }
+
+        // Property: Refunds, shares and the final close only happen once settled
+        let settled_only = action == BucketAction::Refund
+            || action == BucketAction::ClaimShare
+            || action == BucketAction::CloseSettled;
+        if settled_only && result.is_ok() {
+            assert_eq!(stored, BucketStatus::Settled);
+        }
+
+        // Property: Claims only succeed once the claim window has elapsed
+        if action == BucketAction::Claim && result.is_ok() {
+            assert!(claim_window_elapsed || stored == BucketStatus::Claimable);
+            assert_eq!(result, Ok(BucketStatus::Settled));
+        }
+
+        // Property: Sides are only configured on an open bucket, which stays open
+        if action == BucketAction::ConfigureSides && result.is_ok() {
+            assert_eq!(status, BucketStatus::Open);
+            assert_eq!(result, Ok(BucketStatus::Open));
+        }
+    }
+
+    // Proof 14: Claim deadline computation never wraps around
+    #[kani::proof]
+    fn verify_claim_deadline_no_wraparound() {
+        let last_flip: u64 = kani::any();
+        let claim_delay: u64 = kani::any();
+
+        let deadline = calculate_claim_deadline(last_flip, claim_delay);
+
+        // Property: A computed deadline is exactly last_flip + claim_delay
+        if let Some(deadline) = deadline {
+            assert!(deadline >= last_flip);
+            assert_eq!(deadline - last_flip, claim_delay);
+        }
+
+        // Property: Overflow is reported instead of wrapping to an early deadline
+        if last_flip > u64::MAX - claim_delay {
+            assert!(deadline.is_none());
+        }
+    }
+
+    // Proof 15: The claim window only opens once the full delay has passed
+    #[kani::proof]
+    fn verify_claim_window_respects_delay() {
+        let now: u64 = kani::any();
+        let last_flip: u64 = kani::any();
+        let claim_delay: u64 = kani::any();
+
+        let elapsed = is_claim_window_elapsed(now, last_flip, claim_delay);
+
+        // Property: Elapsed implies at least claim_delay units since the last flip
+        if elapsed {
+            assert!(now >= last_flip);
+            assert!(now - last_flip >= claim_delay);
+        }
+
+        // Property: Once the full delay has passed without overflow, the window is open
+        if now >= last_flip && now - last_flip >= claim_delay {
+            assert!(elapsed);
+        }
+    }
+
+    // Proof 16: Claim delay validation matches the per-unit bounds
+    #[kani::proof]
+    fn verify_claim_delay_validation() {
+        let claim_delay: u64 = kani::any();
+        let unit_index: u8 = kani::any();
+        kani::assume(unit_index <= 2);
+        let unit = ClaimDelayUnit::try_from(unit_index).unwrap();
+
+        let is_valid = validate_claim_delay(unit, claim_delay);
+
+        // Property: Zero delay is never valid (payout could be claimed in the flip's own epoch/slot/second)
+        if claim_delay == 0 {
+            assert!(!is_valid);
+        }
+
+        // Property: Valid delays stay within the largest bound
+        if is_valid {
+            assert!(claim_delay <= MAX_CLAIM_DELAY_SLOTS);
+        }
+    }
+
+    // Proof 17: Per-bucket minimum deposit can never undercut the program floor
+    #[kani::proof]
+    fn verify_min_deposit_validation() {
+        let min_deposit: u64 = kani::any();
+        let amount: u64 = kani::any();
+
+        let is_valid = validate_min_deposit(min_deposit);
+
+        // Property: Valid iff at or above the program-wide floor
+        assert_eq!(is_valid, min_deposit >= MIN_DEPOSIT_FLOOR);
+
+        // Property: Any deposit accepted by a valid bucket is at least the floor
+        if is_valid && meets_min_deposit(amount, min_deposit) {
+            assert!(amount >= MIN_DEPOSIT_FLOOR);
+            assert!(amount > 0);
+        }
+    }
+
+    // Proof 18: Per-bucket minimum initial swap can never undercut the program floor
+    #[kani::proof]
+    fn verify_min_initial_swap_validation() {
+        let min_initial_swap: u64 = kani::any();
+        let initial_last_swap: u64 = kani::any();
+
+        let is_valid = validate_min_initial_swap(min_initial_swap);
+
+        // Property: Valid iff at or above the program-wide floor
+        assert_eq!(is_valid, min_initial_swap >= MIN_INITIAL_SWAP_FLOOR);
+
+        // Property: An accepted initial swap respects both the bucket minimum and the floor
+        if is_valid && meets_min_initial_swap(initial_last_swap, min_initial_swap) {
+            assert!(initial_last_swap >= min_initial_swap);
+            assert!(initial_last_swap >= MIN_INITIAL_SWAP_FLOOR);
+        }
+    }
+
+    // Proof 19: The computed flip deposit always reaches the threshold
+    #[kani::proof]
+    fn verify_flip_deposit_reaches_threshold() {
+        let flushable: u64 = kani::any();
+        let threshold: u64 = kani::any();
+        let min_deposit: u64 = kani::any();
+
+        let deposit = calculate_flip_deposit(flushable, threshold, min_deposit);
+
+        // Property: Nothing is deposited when the escrow already meets the threshold
+        if flushable >= threshold {
+            assert_eq!(deposit, 0);
+        } else {
+            // Property: The deposit respects the bucket minimum
+            assert!(deposit >= min_deposit);
+
+            // Property: Flushable balance after the deposit meets the threshold
+            // (checked in u128: the deposit may be bumped up to a huge min_deposit)
+            assert!(flushable as u128 + deposit as u128 >= threshold as u128);
+        }
+    }
+
+    // Proof 20: A passing swap guard means the bucket matches every expectation given
+    #[kani::proof]
+    fn verify_swap_guard() {
+        let guard = SwapGuard {
+            expected_last_swap: if kani::any() { Some(kani::any()) } else { None },
+            expected_current_target: if kani::any() { Some(kani::any()) } else { None },
+            max_lamports: if kani::any() { Some(kani::any()) } else { None },
+        };
+        let last_swap: u64 = kani::any();
+        let current_target: Pubkey = kani::any();
+        let lamports: u64 = kani::any();
+
+        let result = guard.check(last_swap, &current_target, lamports);
+
+        // Property: An empty guard never rejects
+        if guard == SwapGuard::default() {
+            assert!(result.is_ok());
+        }
+
+        // Property: Success implies every supplied expectation holds
+        if result.is_ok() {
+            assert!(guard.expected_last_swap.map_or(true, |e| e == last_swap));
+            assert!(guard.expected_current_target.map_or(true, |e| e == current_target));
+            assert!(guard.max_lamports.map_or(true, |m| lamports <= m));
+        }
+    }
+
+    // Proof 21: A reinforcement always blocks closing, even without any flip
+    #[kani::proof]
+    fn verify_reinforce_blocks_close() {
+        let reinforce_count: u64 = kani::any();
+        let total_flushed: u64 = kani::any();
+        let reinforced: u64 = kani::any();
+
+        // A reinforcement must meet the flush threshold, which is never zero
+        kani::assume(reinforced > 0);
+
+        if let Some((new_count, new_total)) = record_reinforce(reinforce_count, total_flushed, reinforced) {
+            // Property: Counters only grow
+            assert_eq!(new_count, reinforce_count + 1);
+            assert_eq!(new_total, total_flushed + reinforced);
+
+            // Property: The creator can no longer close, whatever the flip count
+            assert!(!can_close_bucket(0, new_total));
+        }
+    }
+
+    // Proof 22: Side count validation matches the supported range
+    #[kani::proof]
+    fn verify_side_count_validation() {
+        let side_count: u8 = kani::any();
+
+        // Property: Valid iff between 2 and 16 sides
+        assert_eq!(validate_side_count(side_count), (2..=16).contains(&side_count));
+    }
+
+    // Proof 23: Duplicate side addresses are always detected
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn verify_unique_addresses() {
+        let addresses: [Pubkey; 3] = kani::any();
+
+        let unique = has_unique_addresses(&addresses);
+
+        // Property: Unique iff every pair differs
+        assert_eq!(
+            unique,
+            addresses[0] != addresses[1] && addresses[0] != addresses[2] && addresses[1] != addresses[2]
+        );
+    }
+
+    // Proof 24: Deposit records never lose or invent contributions
+    #[kani::proof]
+    fn verify_deposit_record_conservation() {
+        let total_flushed: u64 = kani::any();
+        let pending: u64 = kani::any();
+        let record_flush_count: u64 = kani::any();
+        let side_flush_count: u64 = kani::any();
+        let amount: u64 = kani::any();
+
+        // Invariant held by every stored record
+        let total_contributed = match total_flushed.checked_add(pending) {
+            Some(total) => total,
+            None => return,
+        };
+
+        if let Some((flushed, still_pending)) =
+            settle_deposit_record(total_flushed, pending, record_flush_count, side_flush_count)
+        {
+            // Property: Settling only moves lamports from pending to flushed
+            assert_eq!(flushed + still_pending, total_contributed);
+            assert!(flushed >= total_flushed);
+
+            // Property: Nothing stays pending once the side has been flushed again
+            if side_flush_count != record_flush_count {
+                assert_eq!(still_pending, 0);
+            }
+
+            // Property: A deposit keeps contributed == flushed + pending
+            if let Some((contributed, pending_after)) = record_deposit(total_contributed, still_pending, amount) {
+                assert_eq!(contributed, flushed + pending_after);
+            }
+        }
+    }
+
+    // Proof 25: Pro-rata refunds never pay out more than the escrow holds
+    #[kani::proof]
+    fn verify_pro_rata_refund() {
+        let pool: u64 = kani::any();
+        let first: u64 = kani::any();
+        let second: u64 = kani::any();
+
+        // Two depositors share the side's pending total
+        let side_pending = match first.checked_add(second) {
+            Some(total) => total,
+            None => return,
+        };
+
+        let first_refund = calculate_pro_rata_share(pool, first, side_pending);
+
+        // Property: A single refund never exceeds the pool
+        assert!(first_refund <= pool);
+
+        // Property: The second (last) depositor takes exactly what is left, dust included
+        let second_refund = calculate_pro_rata_share(pool - first_refund, second, side_pending - first);
+        assert_eq!(first_refund + second_refund, pool);
+    }
+
+    // Proof 26: Withdrawals stay within the receipt and never dip into the rent reserve
+    #[kani::proof]
+    fn verify_withdrawable() {
+        let pending: u64 = kani::any();
+        let escrow_balance: u64 = kani::any();
+        let rent_exempt_reserve: u64 = kani::any();
+
+        let flushable = calculate_flushable_balance(escrow_balance, rent_exempt_reserve);
+        let withdrawable = calculate_withdrawable(pending, flushable);
+
+        // Property: Never more than the depositor's pending receipt
+        assert!(withdrawable <= pending);
+
+        // Property: The escrow keeps at least its rent-exempt reserve (when it had it)
+        assert!(withdrawable <= escrow_balance);
+        if escrow_balance >= rent_exempt_reserve {
+            assert!(escrow_balance - withdrawable >= rent_exempt_reserve);
+        }
+    }
+
+    // Proof 27: Proportional payout conserves the pot down to the last lamport
+    // Loop-free, but the symbolic u128 divisions are slow under the default solver
+    #[kani::proof]
+    #[kani::solver(kissat)]
+    fn verify_proportional_payout_conservation() {
+        let total: u64 = kani::any();
+        let creator_fee_bps: u16 = kani::any();
+        let claimer_fee_bps: u16 = kani::any();
+        let first: u64 = kani::any();
+        let second: u64 = kani::any();
+
+        kani::assume(validate_fees(creator_fee_bps, claimer_fee_bps));
+
+        // Two contributors make up the winning side's flushed contributions
+        let contributions = match first.checked_add(second) {
+            Some(contributions) => contributions,
+            None => return,
+        };
+
+        if let Some((creator_cut, claimer_cut, winner_cut)) =
+            calculate_payout_distribution(total, creator_fee_bps, claimer_fee_bps)
+        {
+            let first_share = calculate_pro_rata_share(winner_cut, first, contributions);
+            let second_share = calculate_pro_rata_share(winner_cut - first_share, second, contributions - first);
+
+            // Property: Fees plus every contributor's share add up to the pot exactly
+            assert_eq!(creator_cut + claimer_cut + first_share + second_share, total);
+
+            // Property: No contributor is paid more than their proportion (rounded down) until the last
+            if contributions > 0 && first < contributions {
+                assert!(first_share as u128 * contributions as u128 <= winner_cut as u128 * first as u128);
+            }
+        }
+    }
+
+    // Proof 28: Weighted split among a side's recipients always sums to the winner's cut
+    #[kani::proof]
+    #[kani::unwind(9)]
+    fn verify_weighted_split_conservation() {
+        let amount: u64 = kani::any();
+        let weights_bps: [u16; 3] = kani::any();
+
+        let result = calculate_weighted_split(amount, &weights_bps);
+
+        // Property: Valid weights always split, invalid ones never do
+        assert_eq!(result.is_some(), validate_side_weights(&weights_bps));
+
+        // Property: Cuts sum back to amount and unused entries stay zero
+        if let Some(cuts) = result {
+            let total = cuts.iter().fold(0u128, |sum, &cut| sum + cut as u128);
+            assert_eq!(total, amount as u128);
+            assert!(cuts[3..].iter().all(|&cut| cut == 0));
+        }
+    }
+
+    // Proof 29: A random starting side is always one of the bucket's sides
+    #[kani::proof]
+    fn verify_random_side_in_range() {
+        let slot_hash: [u8; 32] = kani::any();
+        let side_count: u8 = kani::any();
+
+        let result = select_random_side(&slot_hash, side_count);
+
+        // Property: Only valid side counts produce a side, and it is always in range
+        assert_eq!(result.is_some(), validate_side_count(side_count));
+        if let Some(side) = result {
+            assert!(side < side_count);
+        }
+    }
+
+    // Proof 30: A settled bucket only counts as drained once it owes nothing
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn verify_settlement_drained() {
+        let side_pending: [u64; 3] = kani::any();
+        let share_pool: u64 = kani::any();
+        let unclaimed_contributions: u64 = kani::any();
+
+        let drained = is_settlement_drained(&side_pending, share_pool, unclaimed_contributions);
+
+        // Property: Drained means no pending deposit, share pool or unclaimed contribution is left
+        let owes_nothing = side_pending[0] | side_pending[1] | side_pending[2] | share_pool | unclaimed_contributions == 0;
+        assert_eq!(drained, owes_nothing);
+    }
 }
 
 #[cfg(test)]
@@ -288,6 +1055,338 @@ mod tests {
         assert_eq!(result, Some(1_500_000_000));
     }
 
+    #[test]
+    fn test_flush_target() {
+        let address_a: Pubkey = [1u8; 32];
+        let address_b: Pubkey = [2u8; 32];
+
+        // Flushing the challenger's escrow hands control to the challenger
+        assert_eq!(resolve_flush_target(&address_b, &address_a), Some(address_b));
+        assert_eq!(resolve_flush_target(&address_a, &address_b), Some(address_a));
+
+        // Flushing the controlling side's escrow must not hand control to the opponent
+        assert_eq!(resolve_flush_target(&address_a, &address_a), None);
+        assert_eq!(resolve_flush_target(&address_b, &address_b), None);
+    }
+
+    #[test]
+    fn test_flushable_balance() {
+        let rent_exempt_reserve = 890_880;
+
+        assert_eq!(calculate_flushable_balance(890_880 + 1_050_000_000, rent_exempt_reserve), 1_050_000_000);
+        assert_eq!(calculate_flushable_balance(890_880, rent_exempt_reserve), 0);
+        assert_eq!(calculate_flushable_balance(0, rent_exempt_reserve), 0);
+    }
+
+    #[test]
+    fn test_flip_deposit() {
+        let min_deposit = 1_000;
+
+        // Escrow holds 0.5 SOL against a 1.05 SOL threshold: deposit exactly the difference
+        assert_eq!(calculate_flip_deposit(500_000_000, 1_050_000_000, min_deposit), 550_000_000);
+
+        // A shortfall below the bucket minimum is rounded up to the minimum
+        assert_eq!(calculate_flip_deposit(1_049_999_999, 1_050_000_000, min_deposit), min_deposit);
+
+        // Escrow already meets the threshold: flush without depositing
+        assert_eq!(calculate_flip_deposit(1_050_000_000, 1_050_000_000, min_deposit), 0);
+        assert_eq!(calculate_flip_deposit(2_000_000_000, 1_050_000_000, min_deposit), 0);
+    }
+
+    #[test]
+    fn test_swap_guard() {
+        let address_a: Pubkey = [1u8; 32];
+        let address_b: Pubkey = [2u8; 32];
+
+        // No expectations: always passes
+        assert_eq!(SwapGuard::default().check(1_000_000_000, &address_a, 2_000_000_000), Ok(()));
+
+        // Supporter of B signed against last_swap 1 SOL with A in control, paying at most 1.1 SOL
+        let guard = SwapGuard {
+            expected_last_swap: Some(1_000_000_000),
+            expected_current_target: Some(address_a),
+            max_lamports: Some(1_100_000_000),
+        };
+        assert_eq!(guard.check(1_000_000_000, &address_a, 1_050_000_000), Ok(()));
+
+        // The other side flipped first in the same slot
+        assert_eq!(guard.check(1_050_000_000, &address_a, 1_050_000_000), Err(HateFunError::LastSwapMismatch));
+        assert_eq!(guard.check(1_000_000_000, &address_b, 1_050_000_000), Err(HateFunError::CurrentTargetMismatch));
+        assert_eq!(guard.check(1_000_000_000, &address_a, 1_100_000_001), Err(HateFunError::MaxLamportsExceeded));
+    }
+
+    #[test]
+    fn test_many_consecutive_flips_keep_escrows_alive() {
+        // Simulate a long back-and-forth: every flush leaves exactly the reserve behind,
+        // so both escrows stay rent-exempt and can keep receiving deposits
+        let rent_exempt_reserve = 890_880;
+        let min_increase_bps = 500;
+        let address_a: Pubkey = [1u8; 32];
+        let address_b: Pubkey = [2u8; 32];
+
+        let mut escrows = [rent_exempt_reserve, rent_exempt_reserve];
+        let mut main_bucket = rent_exempt_reserve;
+        let mut last_swap = 1_000_000_000;
+        let mut current_target = address_a;
+
+        for flip in 0..50 {
+            let side = if flip % 2 == 0 { 1 } else { 0 };
+            let flushed_side = if side == 0 { address_a } else { address_b };
+
+            let threshold = calculate_flush_threshold(last_swap, min_increase_bps).unwrap();
+            escrows[side] += threshold;
+
+            let flushable = calculate_flushable_balance(escrows[side], rent_exempt_reserve);
+            assert!(flushable >= threshold);
+
+            current_target = resolve_flush_target(&flushed_side, &current_target).unwrap();
+            escrows[side] -= flushable;
+            main_bucket += flushable;
+            last_swap = flushable;
+
+            assert_eq!(escrows[side], rent_exempt_reserve);
+            assert_eq!(current_target, flushed_side);
+        }
+
+        assert_eq!(escrows, [rent_exempt_reserve, rent_exempt_reserve]);
+        assert!(main_bucket > 50 * 1_000_000_000);
+    }
+
+    #[test]
+    fn test_close_gated_on_flip_counters() {
+        // Fresh bucket can be closed
+        assert!(can_close_bucket(0, 0));
+
+        // A single flip (even in the creation epoch) blocks closing
+        let (flip_count, total_flushed) = record_flip(0, 0, 1_050_000_000).unwrap();
+        assert_eq!((flip_count, total_flushed), (1, 1_050_000_000));
+        assert!(!can_close_bucket(flip_count, total_flushed));
+
+        // Counters never wrap around back to a closable state
+        assert_eq!(record_flip(u64::MAX, 0, 1), None);
+        assert_eq!(record_flip(0, u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_reinforce_counters() {
+        // Reinforcing before any flip still puts funds in the pot, so close is blocked
+        let (reinforce_count, total_flushed) = record_reinforce(0, 0, 1_050_000_000).unwrap();
+        assert_eq!((reinforce_count, total_flushed), (1, 1_050_000_000));
+        assert!(!can_close_bucket(0, total_flushed));
+
+        assert_eq!(record_reinforce(u64::MAX, 0, 1), None);
+        assert_eq!(record_reinforce(0, u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn test_side_validation() {
+        assert!(!validate_side_count(1));
+        assert!(validate_side_count(2));
+        assert!(validate_side_count(16));
+        assert!(!validate_side_count(17));
+
+        let sides: [Pubkey; 3] = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        assert!(has_unique_addresses(&sides));
+        assert!(!has_unique_addresses(&[sides[0], sides[1], sides[0]]));
+    }
+
+    #[test]
+    fn test_select_random_side() {
+        let mut slot_hash = [0xAAu8; 32];
+        slot_hash[..8].copy_from_slice(&7u64.to_le_bytes());
+        assert_eq!(select_random_side(&slot_hash, 2), Some(1));
+        assert_eq!(select_random_side(&slot_hash, 3), Some(1));
+        assert_eq!(select_random_side(&slot_hash, 16), Some(7));
+        assert_eq!(select_random_side(&slot_hash, 1), None);
+        assert_eq!(select_random_side(&slot_hash, 17), None);
+    }
+
+    #[test]
+    fn test_deposit_record_accounting() {
+        // Two deposits before any flush stay pending
+        let (contributed, pending) = record_deposit(0, 0, 1_000_000_000).unwrap();
+        let (contributed, pending) = record_deposit(contributed, pending, 500_000_000).unwrap();
+        assert_eq!((contributed, pending), (1_500_000_000, 1_500_000_000));
+
+        // Side not flushed since the last update: nothing changes
+        assert_eq!(settle_deposit_record(0, pending, 3, 3), Some((0, 1_500_000_000)));
+
+        // Side flushed since: everything pending was swept into the main bucket
+        let (flushed, pending) = settle_deposit_record(0, pending, 3, 4).unwrap();
+        assert_eq!((flushed, pending), (1_500_000_000, 0));
+
+        let (contributed, pending) = record_deposit(contributed, pending, 250_000_000).unwrap();
+        assert_eq!(contributed, flushed + pending);
+    }
+
+    #[test]
+    fn test_pro_rata_refund() {
+        // Escrow holds exactly the recorded deposits: everyone gets their pending back
+        assert_eq!(calculate_pro_rata_share(3_000_000, 1_000_000, 3_000_000), 1_000_000);
+
+        // Extra lamports sent straight to the escrow are shared pro rata
+        assert_eq!(calculate_pro_rata_share(3_300_000, 1_000_000, 3_000_000), 1_100_000);
+
+        // The last depositor takes whatever is left, rounding dust included
+        let first = calculate_pro_rata_share(1_000, 1, 3);
+        let second = calculate_pro_rata_share(1_000 - first, 1, 2);
+        let last = calculate_pro_rata_share(1_000 - first - second, 1, 1);
+        assert_eq!((first, second, last), (333, 333, 334));
+    }
+
+    #[test]
+    fn test_proportional_payout() {
+        // 10 SOL pot, 5% creator fee, 0.5% claimer fee
+        let (creator_cut, claimer_cut, winner_cut) = calculate_payout_distribution(10_000_000_000, 500, 50).unwrap();
+        assert_eq!(winner_cut, 9_450_000_000);
+
+        // Winning side contributions: 3 SOL, 1 SOL and 2 SOL, claimed in that order
+        let mut pool = winner_cut;
+        let mut unclaimed = 6_000_000_000;
+        let mut shares = Vec::new();
+        for contribution in [3_000_000_000u64, 1_000_000_000, 2_000_000_000] {
+            let share = calculate_pro_rata_share(pool, contribution, unclaimed);
+            pool -= share;
+            unclaimed -= contribution;
+            shares.push(share);
+        }
+
+        assert_eq!(shares, vec![4_725_000_000, 1_575_000_000, 3_150_000_000]);
+        assert_eq!(pool, 0);
+        assert_eq!(creator_cut + claimer_cut + shares.iter().sum::<u64>(), 10_000_000_000);
+    }
+
+    #[test]
+    fn test_weighted_split() {
+        // Three charities at 50/30/20: the first one absorbs the rounding dust
+        let cuts = calculate_weighted_split(1_000_000_001, &[5_000, 3_000, 2_000]).unwrap();
+        assert_eq!(cuts[..3], [500_000_001, 300_000_000, 200_000_000]);
+        assert_eq!(cuts.iter().sum::<u64>(), 1_000_000_001);
+
+        // Weights must be non-zero and sum to exactly 100%
+        assert!(validate_side_weights(&[10_000]));
+        assert!(!validate_side_weights(&[]));
+        assert!(!validate_side_weights(&[5_000, 4_999]));
+        assert!(!validate_side_weights(&[10_000, 0]));
+        assert!(!validate_side_weights(&[1_250; 9]));
+        assert_eq!(calculate_weighted_split(1_000, &[6_000, 5_000]), None);
+    }
+
+    #[test]
+    fn test_withdrawable() {
+        // Escrow holds the whole receipt: all of it can be withdrawn
+        assert_eq!(calculate_withdrawable(1_000_000, 5_000_000), 1_000_000);
+
+        // Escrow holds less than the receipt (e.g. others already refunded): capped by the escrow
+        assert_eq!(calculate_withdrawable(1_000_000, 400_000), 400_000);
+        assert_eq!(calculate_withdrawable(1_000_000, 0), 0);
+    }
+
+    #[test]
+    fn test_bucket_lifecycle() {
+        // Open bucket accepts deposits and flips but cannot be claimed yet
+        let status = effective_status(BucketStatus::Open, false);
+        assert_eq!(next_status(status, BucketAction::Deposit), Ok(BucketStatus::Open));
+        assert_eq!(next_status(status, BucketAction::Flush), Ok(BucketStatus::Open));
+        assert_eq!(next_status(status, BucketAction::Reinforce), Ok(BucketStatus::Open));
+        assert_eq!(next_status(status, BucketAction::Withdraw), Ok(BucketStatus::Open));
+        assert_eq!(next_status(status, BucketAction::Claim), Err(HateFunError::ClaimTooEarly));
+        assert_eq!(next_status(status, BucketAction::ConfigureSides), Ok(BucketStatus::Open));
+
+        // Once the window elapses the bucket is claimable; a flip reopens it
+        let status = effective_status(BucketStatus::Open, true);
+        assert_eq!(status, BucketStatus::Claimable);
+        assert_eq!(next_status(status, BucketAction::Reinforce), Ok(BucketStatus::Claimable));
+        assert_eq!(next_status(status, BucketAction::Flush), Ok(BucketStatus::Open));
+        assert_eq!(next_status(status, BucketAction::Claim), Ok(BucketStatus::Settled));
+        assert_eq!(next_status(status, BucketAction::ConfigureSides), Err(HateFunError::SideConfigLocked));
+
+        // Settled buckets reject deposits, flushes and second claims
+        for action in [BucketAction::Deposit, BucketAction::Flush, BucketAction::Reinforce, BucketAction::Claim, BucketAction::Close, BucketAction::Withdraw, BucketAction::ConfigureSides] {
+            assert_eq!(next_status(BucketStatus::Settled, action), Err(HateFunError::BucketSettled));
+            assert_eq!(next_status(BucketStatus::Closed, action), Err(HateFunError::BucketClosed));
+        }
+
+        // Pending deposits can only be refunded after settlement
+        assert_eq!(next_status(BucketStatus::Settled, BucketAction::Refund), Ok(BucketStatus::Settled));
+        assert_eq!(next_status(BucketStatus::Open, BucketAction::Refund), Err(HateFunError::BucketNotSettled));
+        assert_eq!(next_status(BucketStatus::Closed, BucketAction::Refund), Err(HateFunError::BucketClosed));
+
+        // Proportional shares are claimed the same way
+        assert_eq!(next_status(BucketStatus::Settled, BucketAction::ClaimShare), Ok(BucketStatus::Settled));
+        assert_eq!(next_status(BucketStatus::Claimable, BucketAction::ClaimShare), Err(HateFunError::BucketNotSettled));
+
+        // A settled bucket is closed last; it never skips settlement
+        assert_eq!(next_status(BucketStatus::Settled, BucketAction::CloseSettled), Ok(BucketStatus::Closed));
+        assert_eq!(next_status(BucketStatus::Claimable, BucketAction::CloseSettled), Err(HateFunError::BucketNotSettled));
+        assert_eq!(next_status(BucketStatus::Closed, BucketAction::CloseSettled), Err(HateFunError::BucketClosed));
+    }
+
+    #[test]
+    fn test_settlement_drained() {
+        assert!(is_settlement_drained(&[0, 0, 0], 0, 0));
+        assert!(!is_settlement_drained(&[0, 1, 0], 0, 0), "Pending deposits still to refund");
+        assert!(!is_settlement_drained(&[0, 0], 1, 0), "Share pool not yet claimed");
+        assert!(!is_settlement_drained(&[0, 0], 0, 1), "Contributions with unclaimed shares");
+    }
+
+    #[test]
+    fn test_can_close_deposit_record() {
+        assert!(can_close_deposit_record(0, false, false));
+        assert!(can_close_deposit_record(500, true, false), "Pending swept by the claim is gone anyway");
+        assert!(!can_close_deposit_record(500, false, false), "Pending deposit still to withdraw or refund");
+        assert!(!can_close_deposit_record(0, false, true), "Share still to claim");
+    }
+
+    #[test]
+    fn test_min_deposit_validation() {
+        assert!(validate_min_deposit(MIN_DEPOSIT_FLOOR));
+        assert!(validate_min_deposit(1_000_000_000)); // 1 SOL minimum for a high-stakes bucket
+        assert!(!validate_min_deposit(999));
+
+        assert!(meets_min_deposit(1_000_000_000, 1_000_000_000));
+        assert!(!meets_min_deposit(999_999_999, 1_000_000_000));
+    }
+
+    #[test]
+    fn test_min_initial_swap_validation() {
+        assert!(validate_min_initial_swap(MIN_INITIAL_SWAP_FLOOR));
+        assert!(!validate_min_initial_swap(99_999));
+
+        assert!(meets_min_initial_swap(1_000_000_000, 500_000_000));
+        assert!(!meets_min_initial_swap(400_000_000, 500_000_000));
+    }
+
+    #[test]
+    fn test_claim_delay_validation() {
+        assert!(validate_claim_delay(ClaimDelayUnit::Epochs, 3));
+        assert!(validate_claim_delay(ClaimDelayUnit::Epochs, MAX_CLAIM_DELAY_EPOCHS));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Epochs, 0));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Epochs, MAX_CLAIM_DELAY_EPOCHS + 1));
+
+        // ~1 hour of slots is a valid short campaign
+        assert!(validate_claim_delay(ClaimDelayUnit::Slots, 9_000));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Slots, MIN_CLAIM_DELAY_SLOTS - 1));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Slots, MAX_CLAIM_DELAY_SLOTS + 1));
+
+        // Wall-clock mode: 6 hours is valid, zero is not
+        assert!(validate_claim_delay(ClaimDelayUnit::Seconds, 6 * 3_600));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Seconds, 0));
+        assert!(!validate_claim_delay(ClaimDelayUnit::Seconds, MAX_CLAIM_DELAY_SECONDS + 1));
+    }
+
+    #[test]
+    fn test_claim_window() {
+        // Default 3 epoch window
+        assert!(!is_claim_window_elapsed(101, 100, 3));
+        assert!(!is_claim_window_elapsed(102, 100, 3));
+        assert!(is_claim_window_elapsed(103, 100, 3));
+
+        // Overflowing deadline never opens
+        assert_eq!(calculate_claim_deadline(u64::MAX, 1), None);
+        assert!(!is_claim_window_elapsed(u64::MAX, u64::MAX, 1));
+    }
+
     #[test]
     fn test_payout_distribution() {
         // Test with 5% creator fee, 0.5% claimer fee
@@ -1,11 +1,17 @@
-use solana_client::rpc_client::RpcClient;
+// Instruction builders mirror the on-chain account and data layouts one-to-one
+#![allow(clippy::too_many_arguments)]
+#![allow(deprecated)] // solana_sdk::system_program
+
+use hate_fun::error::HateFunError;
+use hate_fun::state::{Bucket, BucketStatus, DepositRecord, SideConfig};
+use solana_client::{client_error::Result as ClientResult, rpc_client::RpcClient};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
-    system_program,
-    transaction::Transaction,
+    signature::{Keypair, Signature, Signer},
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
 };
 use std::str::FromStr;
 
@@ -38,6 +44,31 @@ fn derive_escrow_b_pda(program_id: &Pubkey, bucket: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"escrow_b", bucket.as_ref()], program_id)
 }
 
+/// Derive the escrow PDA of side 2 and up
+fn derive_escrow_pda(program_id: &Pubkey, bucket: &Pubkey, side: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"escrow", bucket.as_ref(), &[side]], program_id)
+}
+
+/// Derive the SideConfig PDA of one side of a bucket
+fn derive_side_config_pda(program_id: &Pubkey, bucket: &Pubkey, side: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"side_config", bucket.as_ref(), &[side]], program_id)
+}
+
+/// Derive a finalized bucket's Settlement PDA from the bucket's creation slot
+fn derive_settlement_pda(program_id: &Pubkey, bucket: &Pubkey, bucket_creation_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"settlement", bucket.as_ref(), &bucket_creation_slot.to_le_bytes()], program_id)
+}
+
+/// Derive a depositor's DepositRecord PDA for one side of a bucket
+fn derive_deposit_record_pda(program_id: &Pubkey, bucket: &Pubkey, side: u8, depositor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"deposit", bucket.as_ref(), &[side], depositor.as_ref()], program_id)
+}
+
+/// Claim delay units
+const DELAY_UNIT_EPOCHS: u8 = 0;
+const DELAY_UNIT_SLOTS: u8 = 1;
+const DELAY_UNIT_SECONDS: u8 = 2;
+
 /// Build create_bucket instruction
 fn create_bucket_instruction(
     program_id: &Pubkey,
@@ -54,6 +85,10 @@ fn create_bucket_instruction(
     initial_last_swap: u64,
     min_increase_bps: u16,
     seed: &[u8; 32],
+    claim_delay_unit: u8,
+    claim_delay: u64,
+    min_deposit: u64,
+    min_initial_swap: u64,
 ) -> Instruction {
     let mut data = vec![0u8]; // Discriminator 0
     data.extend_from_slice(address_a.as_ref());
@@ -64,11 +99,16 @@ fn create_bucket_instruction(
     data.extend_from_slice(&initial_last_swap.to_le_bytes());
     data.extend_from_slice(&min_increase_bps.to_le_bytes());
     data.extend_from_slice(seed);
+    data.push(claim_delay_unit);
+    data.extend_from_slice(&claim_delay.to_le_bytes());
+    data.extend_from_slice(&min_deposit.to_le_bytes());
+    data.extend_from_slice(&min_initial_swap.to_le_bytes());
 
     Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*creator_address, true),
             AccountMeta::new(*bucket, false),
             AccountMeta::new(*main_bucket, false),
             AccountMeta::new(*escrow_a, false),
@@ -79,21 +119,113 @@ fn create_bucket_instruction(
     }
 }
 
+/// Extend a create_bucket instruction with sides 2 and up and their escrows
+fn with_extra_sides(mut instruction: Instruction, extra_sides: &[Pubkey], extra_escrows: &[Pubkey]) -> Instruction {
+    instruction.data.push(2 + extra_sides.len() as u8);
+    for side in extra_sides {
+        instruction.data.extend_from_slice(side.as_ref());
+    }
+    for escrow in extra_escrows {
+        instruction.accounts.push(AccountMeta::new(*escrow, false));
+    }
+    instruction
+}
+
+/// Refund policy: unflushed escrow balances go back to their depositors after the claim
+const REFUND_POLICY_SWEEP: u8 = 0;
+const REFUND_POLICY_REFUND: u8 = 1;
+
+/// Payout mode: winning contributors share the winner's cut through ClaimShare
+const PAYOUT_MODE_PROPORTIONAL: u8 = 1;
+
+/// Starting side: drawn from SlotHashes at creation instead of chosen
+const STARTING_SIDE_RANDOM: u8 = Bucket::STARTING_SIDE_RANDOM;
+
+/// Append the trailing option bytes to a create_bucket instruction (after any extra sides):
+/// refund_policy, withdrawals_disabled, payout_mode, starting_side, in that order
+fn with_bucket_options(mut instruction: Instruction, options: &[u8]) -> Instruction {
+    // The options follow the side list, so a plain two-sided bucket spells out its side count
+    if instruction.data.len() == 1 + 167 {
+        instruction.data.push(2);
+    }
+    instruction.data.extend_from_slice(options);
+    instruction
+}
+
+/// Side selectors
+const SIDE_A: u8 = 0;
+const SIDE_B: u8 = 1;
+
 /// Build deposit_to_escrow instruction
 fn deposit_to_escrow_instruction(
     program_id: &Pubkey,
     depositor: &Pubkey,
+    bucket: &Pubkey,
     target_escrow: &Pubkey,
+    side: u8,
     amount: u64,
 ) -> Instruction {
     let mut data = vec![1u8]; // Discriminator 1
     data.extend_from_slice(&amount.to_le_bytes());
+    data.push(side);
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(*target_escrow, false),
+            AccountMeta::new(deposit_record, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Encode the optional swap guard appended to deposit / flush instruction data
+fn swap_guard_data(expected_last_swap: Option<u64>, expected_current_target: Option<&Pubkey>, max_lamports: Option<u64>) -> Vec<u8> {
+    let mut flags = 0u8;
+    let mut data = vec![0u8; 49];
+    if let Some(last_swap) = expected_last_swap {
+        flags |= 1;
+        data[1..9].copy_from_slice(&last_swap.to_le_bytes());
+    }
+    if let Some(target) = expected_current_target {
+        flags |= 2;
+        data[9..41].copy_from_slice(target.as_ref());
+    }
+    if let Some(max) = max_lamports {
+        flags |= 4;
+        data[41..49].copy_from_slice(&max.to_le_bytes());
+    }
+    data[0] = flags;
+    data
+}
+
+/// Build deposit_and_flush instruction (amount 0 = deposit exactly what the flip needs)
+fn deposit_and_flush_instruction(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    bucket: &Pubkey,
+    main_bucket: &Pubkey,
+    target_escrow: &Pubkey,
+    side: u8,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![5u8]; // Discriminator 5
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(side);
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
 
     Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(*depositor, true),
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(*main_bucket, false),
             AccountMeta::new(*target_escrow, false),
+            AccountMeta::new(deposit_record, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
@@ -106,8 +238,9 @@ fn flush_escrow_instruction(
     bucket: &Pubkey,
     main_bucket: &Pubkey,
     escrow_to_flush: &Pubkey,
+    side: u8,
 ) -> Instruction {
-    let data = vec![2u8]; // Discriminator 2
+    let data = vec![2u8, side]; // Discriminator 2, then the escrow's side
 
     Instruction {
         program_id: *program_id,
@@ -120,6 +253,27 @@ fn flush_escrow_instruction(
     }
 }
 
+/// Build reinforce instruction
+fn reinforce_instruction(
+    program_id: &Pubkey,
+    bucket: &Pubkey,
+    main_bucket: &Pubkey,
+    escrow_to_reinforce: &Pubkey,
+    side: u8,
+) -> Instruction {
+    let data = vec![6u8, side]; // Discriminator 6, then the escrow's side
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(*main_bucket, false),
+            AccountMeta::new(*escrow_to_reinforce, false),
+        ],
+        data,
+    }
+}
+
 /// Build claim_payout instruction
 fn claim_payout_instruction(
     program_id: &Pubkey,
@@ -130,20 +284,26 @@ fn claim_payout_instruction(
     creator: &Pubkey,
     claimer: &Pubkey,
     winner: &Pubkey,
+    payer: &Pubkey,
+    extra_escrows: &[Pubkey],
 ) -> Instruction {
     let data = vec![3u8]; // Discriminator 3
 
+    let mut accounts = vec![
+        AccountMeta::new(*bucket, false),
+        AccountMeta::new(*main_bucket, false),
+        AccountMeta::new(*escrow_a, false),
+        AccountMeta::new(*escrow_b, false),
+        AccountMeta::new(*creator, false),
+        AccountMeta::new(*claimer, true),
+        AccountMeta::new(*winner, false),
+        AccountMeta::new(*payer, false),
+    ];
+    accounts.extend(extra_escrows.iter().map(|escrow| AccountMeta::new(*escrow, false)));
+
     Instruction {
         program_id: *program_id,
-        accounts: vec![
-            AccountMeta::new(*bucket, false),
-            AccountMeta::new(*main_bucket, false),
-            AccountMeta::new(*escrow_a, false),
-            AccountMeta::new(*escrow_b, false),
-            AccountMeta::new(*creator, false),
-            AccountMeta::new(*claimer, true),
-            AccountMeta::new(*winner, false),
-        ],
+        accounts,
         data,
     }
 }
@@ -156,17 +316,239 @@ fn close_bucket_instruction(
     main_bucket: &Pubkey,
     escrow_a: &Pubkey,
     escrow_b: &Pubkey,
+    extra_escrows: &[Pubkey],
 ) -> Instruction {
     let data = vec![4u8]; // Discriminator 4
 
+    let mut accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new(*bucket, false),
+        AccountMeta::new(*main_bucket, false),
+        AccountMeta::new(*escrow_a, false),
+        AccountMeta::new(*escrow_b, false),
+    ];
+    accounts.extend(extra_escrows.iter().map(|escrow| AccountMeta::new(*escrow, false)));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build refund_pending instruction
+fn refund_pending_instruction(
+    program_id: &Pubkey,
+    bucket: &Pubkey,
+    escrow: &Pubkey,
+    side: u8,
+    depositor: &Pubkey,
+) -> Instruction {
+    let data = vec![7u8]; // Discriminator 7
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(deposit_record, false),
+            AccountMeta::new(*depositor, false),
+        ],
+        data,
+    }
+}
+
+/// Build withdraw_pending instruction
+fn withdraw_pending_instruction(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    bucket: &Pubkey,
+    escrow: &Pubkey,
+    side: u8,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![8u8]; // Discriminator 8
+    data.extend_from_slice(&amount.to_le_bytes());
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new(deposit_record, false),
+        ],
+        data,
+    }
+}
+
+/// Build claim_share instruction
+fn claim_share_instruction(
+    program_id: &Pubkey,
+    bucket: &Pubkey,
+    side: u8,
+    depositor: &Pubkey,
+) -> Instruction {
+    let data = vec![9u8]; // Discriminator 9
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(deposit_record, false),
+            AccountMeta::new(*depositor, false),
+        ],
+        data,
+    }
+}
+
+/// Build create_side_config instruction
+fn create_side_config_instruction(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    bucket: &Pubkey,
+    side: u8,
+    recipients: &[(Pubkey, u16)],
+) -> Instruction {
+    let mut data = vec![10u8]; // Discriminator 10
+    data.push(side);
+    data.push(recipients.len() as u8);
+    for (recipient, weight_bps) in recipients {
+        data.extend_from_slice(recipient.as_ref());
+        data.extend_from_slice(&weight_bps.to_le_bytes());
+    }
+    let (side_config, _) = derive_side_config_pda(program_id, bucket, side);
+
     Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(*creator, true),
             AccountMeta::new(*bucket, false),
-            AccountMeta::new(*main_bucket, false),
-            AccountMeta::new(*escrow_a, false),
-            AccountMeta::new(*escrow_b, false),
+            AccountMeta::new(side_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Append the winning side's config and its recipients to a claim_payout instruction
+fn with_side_config(mut instruction: Instruction, side_config: &Pubkey, recipients: &[Pubkey]) -> Instruction {
+    instruction.accounts.push(AccountMeta::new_readonly(*side_config, false));
+    for recipient in recipients {
+        instruction.accounts.push(AccountMeta::new(*recipient, false));
+    }
+    instruction
+}
+
+/// Append the accounts that close_bucket and close_settled close with the bucket:
+/// the configs of the configured sides in side order (close_settled wants the creator first)
+fn with_closed_side_configs(mut instruction: Instruction, accounts: &[Pubkey]) -> Instruction {
+    instruction.accounts.extend(accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    instruction
+}
+
+/// Build finalize instruction
+fn finalize_instruction(
+    program_id: &Pubkey,
+    claimer: &Pubkey,
+    bucket: &Pubkey,
+    main_bucket: &Pubkey,
+    escrow_a: &Pubkey,
+    escrow_b: &Pubkey,
+    bucket_creation_slot: u64,
+    extra_escrows: &[Pubkey],
+) -> Instruction {
+    let data = vec![11u8]; // Discriminator 11
+    let (settlement, _) = derive_settlement_pda(program_id, bucket, bucket_creation_slot);
+
+    let mut accounts = vec![
+        AccountMeta::new(*claimer, true),
+        AccountMeta::new(*bucket, false),
+        AccountMeta::new(*main_bucket, false),
+        AccountMeta::new(*escrow_a, false),
+        AccountMeta::new(*escrow_b, false),
+        AccountMeta::new(settlement, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(extra_escrows.iter().map(|escrow| AccountMeta::new(*escrow, false)));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build withdraw instruction
+fn withdraw_instruction(
+    program_id: &Pubkey,
+    payee: &Pubkey,
+    settlement: &Pubkey,
+    claimer: &Pubkey,
+    destination: Option<&Pubkey>,
+) -> Instruction {
+    let data = vec![12u8]; // Discriminator 12
+
+    // Without a destination the payee's own account receives the entries and need not sign
+    let mut accounts = vec![
+        AccountMeta::new(*payee, destination.is_some()),
+        AccountMeta::new(*settlement, false),
+        AccountMeta::new(*claimer, false),
+    ];
+    accounts.extend(destination.map(|destination| AccountMeta::new(*destination, false)));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build close_settled instruction
+fn close_settled_instruction(
+    program_id: &Pubkey,
+    bucket: &Pubkey,
+    payer: &Pubkey,
+    escrow_a: &Pubkey,
+    escrow_b: &Pubkey,
+    extra_escrows: &[Pubkey],
+) -> Instruction {
+    let data = vec![13u8]; // Discriminator 13
+
+    let mut accounts = vec![
+        AccountMeta::new(*bucket, false),
+        AccountMeta::new(*payer, false),
+        AccountMeta::new(*escrow_a, false),
+        AccountMeta::new(*escrow_b, false),
+    ];
+    accounts.extend(extra_escrows.iter().map(|escrow| AccountMeta::new(*escrow, false)));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build close_deposit_record instruction
+fn close_deposit_record_instruction(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    bucket: &Pubkey,
+    side: u8,
+) -> Instruction {
+    let data = vec![14u8]; // Discriminator 14
+    let (deposit_record, _) = derive_deposit_record_pda(program_id, bucket, side, depositor);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(*bucket, false),
+            AccountMeta::new(deposit_record, false),
         ],
         data,
     }
@@ -194,6 +576,22 @@ mod tests {
         }
     }
 
+    /// Instruction error a failed transaction returned, if it got as far as an instruction
+    fn instruction_error(result: ClientResult<Signature>) -> Option<InstructionError> {
+        match result.err()?.get_transaction_error()? {
+            TransactionError::InstructionError(_, error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// HateFunError a failed transaction returned, decoded from its custom error code
+    fn program_error(result: ClientResult<Signature>) -> Option<HateFunError> {
+        match instruction_error(result)? {
+            InstructionError::Custom(code) => HateFunError::from_code(code),
+            _ => None,
+        }
+    }
+
     #[test]
     #[ignore] // Run with: cargo test --test integration_client -- --ignored --nocapture
     fn test_create_bucket() {
@@ -239,6 +637,10 @@ mod tests {
             1_000_000_000,    // 1 SOL initial swap
             500,              // 5% min increase
             &seed,
+            DELAY_UNIT_EPOCHS,
+            3,                // 3 epoch claim delay
+            1_000,            // Minimum deposit
+            100_000,          // Minimum initial swap
         );
 
         // Send transaction
@@ -258,61 +660,248 @@ mod tests {
         let bucket_account = client.get_account(&bucket).expect("Bucket account not found");
         println!("Bucket account size: {} bytes", bucket_account.data.len());
         assert_eq!(bucket_account.owner, program_id);
+
+        // The stored creator and seed re-derive the bucket address, proving the creator signed it
+        let state = Bucket::decode(&bucket_account.data).expect("Invalid bucket data");
+        let creator = Pubkey::new_from_array(state.creator_address);
+        assert_eq!(derive_bucket_pda(&program_id, &creator, &state.seed).0, bucket);
     }
 
     #[test]
     #[ignore]
-    fn test_deposit_and_flush() {
-        println!("\n=== Testing: Deposit and Flush ===\n");
+    fn test_starting_side() {
+        println!("\n=== Testing: Chosen and Random Starting Side ===\n");
 
         let client = setup_client();
         let program_id = get_program_id();
-        let payer = Keypair::new();
+        let creator = Keypair::new();
         let address_a = Keypair::new().pubkey();
         let address_b = Keypair::new().pubkey();
-        let depositor = Keypair::new();
 
-        // Airdrop
-        airdrop_if_needed(&client, &payer.pubkey(), 5_000_000_000);
-        airdrop_if_needed(&client, &depositor.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
 
-        // Generate seed
-        let seed: [u8; 32] = rand::random();
+        let create = |starting_side: u8| {
+            let seed: [u8; 32] = rand::random();
+            let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+            let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+            let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+            let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+            let create_ix = with_bucket_options(
+                create_bucket_instruction(
+                    &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                    &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+                ),
+                &[REFUND_POLICY_SWEEP, 0, 0, starting_side],
+            );
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).map(|_| bucket).map_err(|error| program_error(Err(error)))
+        };
+
+        // Creator hands control to side B from the start
+        let bucket = create(SIDE_B).expect("Create with side B failed");
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.current_target, address_b.to_bytes());
+        assert_eq!(state.starting_side, SIDE_B);
+        assert_eq!(state.start_source, 0, "Chosen start records no randomness");
+        assert_eq!(state.start_slot_hash, [0u8; 32]);
+
+        // Random start records the SlotHashes entry it was drawn from
+        let bucket = create(STARTING_SIDE_RANDOM).expect("Create with a random side failed");
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert!(state.starting_side < 2);
+        let sides = [address_a.to_bytes(), address_b.to_bytes()];
+        assert_eq!(state.current_target, sides[state.starting_side as usize]);
+        assert_eq!(state.start_source, 1);
+        assert!(state.start_slot > 0);
+        assert_ne!(state.start_slot_hash, [0u8; 32]);
+
+        // A side the bucket does not have is rejected
+        assert_eq!(create(2), Err(Some(HateFunError::InvalidStartingSide)), "Starting side outside side_count should fail");
+        println!("✓ Starting side chosen, drawn and validated");
+    }
 
-        // Derive PDAs
-        let (bucket, _) = derive_bucket_pda(&program_id, &payer.pubkey(), &seed);
+    #[test]
+    #[ignore]
+    fn test_create_bucket_with_prefunded_pdas() {
+        println!("\n=== Testing: Create Bucket With Pre-Funded PDAs ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let griefer = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &griefer.pubkey(), 1_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
         let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
         let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
         let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
 
-        // Create bucket first
-        println!("Creating bucket...");
+        // Griefer sends dust to the predictable addresses before the bucket exists
+        let escrow_rent = client.get_minimum_balance_for_rent_exemption(0).unwrap();
+        let transfers = [
+            system_instruction::transfer(&griefer.pubkey(), &bucket, 1_000),
+            system_instruction::transfer(&griefer.pubkey(), &main_bucket, escrow_rent),
+            system_instruction::transfer(&griefer.pubkey(), &escrow_a, escrow_rent + 5_000),
+        ];
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&transfers, Some(&griefer.pubkey()), &[&griefer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
         let create_ix = create_bucket_instruction(
-            &program_id,
-            &payer.pubkey(),
-            &bucket,
-            &main_bucket,
-            &escrow_a,
-            &escrow_b,
-            &address_a,
-            &address_b,
-            &payer.pubkey(),
-            500,
-            50,
-            1_000_000_000, // 1 SOL initial
-            500,           // 5% increase
-            &seed,
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator.pubkey(),
+            500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
         );
-
         let recent_blockhash = client.get_latest_blockhash().unwrap();
-        let tx = Transaction::new_signed_with_payer(
-            &[create_ix],
-            Some(&payer.pubkey()),
-            &[&payer],
-            recent_blockhash,
-        );
-        client.send_and_confirm_transaction(&tx).expect("Create failed");
-        println!("✓ Bucket created");
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Creation should survive pre-funded PDAs");
+
+        // Each PDA is program-owned and rent-exempt; dust above the reserve stays in the account
+        let bucket_account = client.get_account(&bucket).unwrap();
+        assert_eq!(bucket_account.owner, program_id);
+        assert_eq!(
+            bucket_account.lamports,
+            client.get_minimum_balance_for_rent_exemption(bucket_account.data.len()).unwrap()
+        );
+        assert_eq!(client.get_account(&main_bucket).unwrap().owner, program_id);
+        assert_eq!(client.get_account(&escrow_a).unwrap().lamports, escrow_rent + 5_000);
+        println!("✓ Bucket created despite pre-funded PDA addresses");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_create_bucket_requires_creator_signature() {
+        println!("\n=== Testing: Create Bucket Requires Creator Signature ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let squatter = Keypair::new();
+        let creator = Keypair::new().pubkey();
+
+        airdrop_if_needed(&client, &squatter.pubkey(), 5_000_000_000);
+
+        // Squatter tries to claim a seed in someone else's namespace
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator, &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let mut ix = create_bucket_instruction(
+            &program_id, &squatter.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator,
+            500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        // The squatter cannot produce the creator's signature
+        ix.accounts[1].is_signer = false;
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&squatter.pubkey()), &[&squatter], recent_blockhash);
+        let result = client.send_and_confirm_transaction(&tx);
+
+        assert_eq!(program_error(result), Some(HateFunError::CreatorMustSign), "Transaction should fail when the creator did not sign");
+        assert!(client.get_account(&bucket).is_err(), "Bucket should not exist");
+        println!("✓ Correctly rejected bucket creation without the creator's signature");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_create_bucket_with_sponsored_payer() {
+        println!("\n=== Testing: Create Bucket With Sponsored Payer ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let sponsor = Keypair::new();
+        let creator = Keypair::new();
+
+        airdrop_if_needed(&client, &sponsor.pubkey(), 5_000_000_000);
+
+        // The creator signs for their namespace while the sponsor pays the rent
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let ix = create_bucket_instruction(
+            &program_id, &sponsor.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator.pubkey(),
+            500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&sponsor.pubkey()), &[&sponsor, &creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Sponsored creation failed");
+
+        // The sponsor is recorded as the payer who gets the reserves back at settlement
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).expect("Invalid bucket data");
+        assert_eq!(Pubkey::new_from_array(state.creator_address), creator.pubkey());
+        assert_eq!(Pubkey::new_from_array(state.payer), sponsor.pubkey());
+        println!("✓ Sponsor paid for a bucket in the creator's namespace");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_deposit_and_flush() {
+        println!("\n=== Testing: Deposit and Flush ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let payer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let depositor = Keypair::new();
+
+        // Airdrop
+        airdrop_if_needed(&client, &payer.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &depositor.pubkey(), 5_000_000_000);
+
+        // Generate seed
+        let seed: [u8; 32] = rand::random();
+
+        // Derive PDAs
+        let (bucket, _) = derive_bucket_pda(&program_id, &payer.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Create bucket first
+        println!("Creating bucket...");
+        let create_ix = create_bucket_instruction(
+            &program_id,
+            &payer.pubkey(),
+            &bucket,
+            &main_bucket,
+            &escrow_a,
+            &escrow_b,
+            &address_a,
+            &address_b,
+            &payer.pubkey(),
+            500,
+            50,
+            1_000_000_000, // 1 SOL initial
+            500,           // 5% increase
+            &seed,
+            DELAY_UNIT_EPOCHS,
+            3,
+            1_000,
+            100_000,
+        );
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[create_ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        client.send_and_confirm_transaction(&tx).expect("Create failed");
+        println!("✓ Bucket created");
 
         // Deposit to escrow B (1.1 SOL to exceed threshold of 1.05 SOL)
         println!("\nDepositing 1.1 SOL to escrow B...");
@@ -320,7 +909,9 @@ mod tests {
         let deposit_ix = deposit_to_escrow_instruction(
             &program_id,
             &depositor.pubkey(),
+            &bucket,
             &escrow_b,
+            SIDE_B,
             deposit_amount,
         );
 
@@ -340,7 +931,7 @@ mod tests {
 
         // Flush escrow B
         println!("\nFlushing escrow B...");
-        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
 
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(
@@ -359,7 +950,9 @@ mod tests {
         println!("Escrow B balance after flush: {} lamports", escrow_b_balance);
         println!("Main bucket balance: {} lamports", main_bucket_balance);
 
-        assert_eq!(escrow_b_balance, 0);
+        // Escrow keeps its rent-exempt reserve so it survives the flush
+        let escrow_reserve = client.get_minimum_balance_for_rent_exemption(0).unwrap();
+        assert_eq!(escrow_b_balance, escrow_reserve);
         assert!(main_bucket_balance >= deposit_amount);
     }
 
@@ -403,6 +996,10 @@ mod tests {
             1_000_000_000,
             500,
             &seed,
+            DELAY_UNIT_EPOCHS,
+            3,
+            1_000,
+            100_000,
         );
 
         let recent_blockhash = client.get_latest_blockhash().unwrap();
@@ -428,6 +1025,7 @@ mod tests {
             &main_bucket,
             &escrow_a,
             &escrow_b,
+            &[],
         );
 
         let recent_blockhash = client.get_latest_blockhash().unwrap();
@@ -449,6 +1047,55 @@ mod tests {
         println!("Recovered: {} lamports", balance_after.saturating_sub(balance_before));
     }
 
+    #[test]
+    #[ignore]
+    fn test_close_bucket_after_same_epoch_flip_rejected() {
+        println!("\n=== Testing: Close Bucket After Same-Epoch Flip ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Flip to B within the creation epoch
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Creator must not be able to close and take the pot
+        let close_ix = close_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        let result = client.send_and_confirm_transaction(&tx);
+
+        assert_eq!(program_error(result), Some(HateFunError::BucketHasFlips), "Close should fail after a flip, even in the creation epoch");
+        assert!(client.get_account(&bucket).is_ok(), "Bucket should still exist");
+        println!("✓ Correctly rejected close after same-epoch flip");
+    }
+
     #[test]
     #[ignore]
     fn test_full_flow() {
@@ -477,7 +1124,7 @@ mod tests {
         println!("Step 1: Creating bucket (initial 1 SOL, target: A)");
         let create_ix = create_bucket_instruction(
             &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
-            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
         );
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
@@ -486,12 +1133,12 @@ mod tests {
 
         // Step 2: B supporter deposits 1.1 SOL and flips to B
         println!("Step 2: B supporter deposits 1.1 SOL to flip control to B");
-        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &escrow_b, 1_100_000_000);
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
         client.send_and_confirm_transaction(&tx).unwrap();
 
-        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(&[flush_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
         client.send_and_confirm_transaction(&tx).unwrap();
@@ -501,12 +1148,12 @@ mod tests {
 
         // Step 3: A supporter counter-flips
         println!("Step 3: A supporter deposits 1.2 SOL to flip back to A");
-        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &escrow_a, 1_200_000_000);
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 1_200_000_000);
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
         client.send_and_confirm_transaction(&tx).unwrap();
 
-        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_a);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_a, SIDE_A);
         let recent_blockhash = client.get_latest_blockhash().unwrap();
         let tx = Transaction::new_signed_with_payer(&[flush_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
         client.send_and_confirm_transaction(&tx).unwrap();
@@ -520,35 +1167,1501 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn test_validation_fees_too_high() {
-        println!("\n=== Testing: Validation - Fees Too High ===\n");
+    fn test_many_consecutive_flips() {
+        println!("\n=== Testing: Many Consecutive Flips ===\n");
 
         let client = setup_client();
         let program_id = get_program_id();
-        let payer = Keypair::new();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
 
-        airdrop_if_needed(&client, &payer.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 50_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 50_000_000_000);
 
         let seed: [u8; 32] = rand::random();
-        let (bucket, _) = derive_bucket_pda(&program_id, &payer.pubkey(), &seed);
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
         let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
         let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
         let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let escrow_reserve = client.get_minimum_balance_for_rent_exemption(0).unwrap();
 
-        // Try to create with 21% total fees (should fail)
-        let ix = create_bucket_instruction(
-            &program_id, &payer.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
-            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &payer.pubkey(),
-            1600, // 16%
-            500,  // 5% = 21% total
-            1_000_000_000, 500, &seed,
+        // Small initial swap so ten 5% escalations stay affordable
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 100_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        let mut last_swap: u64 = 100_000_000;
+        for flip in 0..10 {
+            // B flips first (A starts in control), then the sides alternate
+            let (supporter, escrow, side) = if flip % 2 == 0 {
+                (&supporter_b, &escrow_b, SIDE_B)
+            } else {
+                (&supporter_a, &escrow_a, SIDE_A)
+            };
+            let amount = last_swap * 10_500 / 10_000;
+
+            let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter.pubkey(), &bucket, escrow, side, amount);
+            let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, escrow, side);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter.pubkey()), &[supporter], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).expect("Flip failed");
+
+            // The escrow PDA must survive every flush with exactly its reserve
+            let escrow_account = client.get_account(escrow).expect("Escrow was garbage-collected");
+            assert_eq!(escrow_account.owner, program_id);
+            assert_eq!(escrow_account.lamports, escrow_reserve);
+
+            last_swap = amount;
+            println!("✓ Flip {} landed. Pot: {} SOL", flip + 1, client.get_balance(&main_bucket).unwrap() as f64 / 1e9);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_flush_controlling_side_rejected() {
+        println!("\n=== Testing: Flush Controlling Side's Escrow Is Rejected ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Bucket starts with A in control
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
         );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
 
+        // A supporter overfunds A's escrow past the threshold
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 1_100_000_000);
         let recent_blockhash = client.get_latest_blockhash().unwrap();
-        let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Flushing A's escrow while A controls must not hand control to B
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_a, SIDE_A);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[flush_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
         let result = client.send_and_confirm_transaction(&tx);
 
-        assert!(result.is_err(), "Transaction should fail with fees > 20%");
-        println!("✓ Correctly rejected fees > 20%");
+        assert_eq!(program_error(result), Some(HateFunError::SideAlreadyInControl), "Flushing the controlling side's escrow should fail");
+        assert!(client.get_balance(&escrow_a).unwrap() >= 1_100_000_000, "Escrow A should keep its deposit");
+        println!("✓ Correctly rejected flush of the controlling side's escrow");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_atomic_deposit_and_flush() {
+        println!("\n=== Testing: Atomic Deposit and Flush ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Bucket starts with A in control, threshold 1.05 SOL
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // An explicit amount below the threshold fails and leaves no deposit behind
+        let escrow_b_before = client.get_balance(&escrow_b).unwrap();
+        let ix = deposit_and_flush_instruction(&program_id, &supporter_b.pubkey(), &bucket, &main_bucket, &escrow_b, SIDE_B, 500_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::InsufficientEscrowBalance), "Deposit below threshold should fail");
+        assert_eq!(client.get_balance(&escrow_b).unwrap(), escrow_b_before, "Failed flip must not keep the deposit");
+
+        // Amount 0 deposits exactly the threshold and flips in one instruction
+        let ix = deposit_and_flush_instruction(&program_id, &supporter_b.pubkey(), &bucket, &main_bucket, &escrow_b, SIDE_B, 0);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        assert_eq!(client.get_balance(&escrow_b).unwrap(), escrow_b_before, "Escrow B should be back at its reserve");
+        assert!(client.get_balance(&main_bucket).unwrap() >= 1_050_000_000, "Main bucket should hold the flip");
+
+        // B now controls: depositing and flushing for B again is rejected
+        let ix = deposit_and_flush_instruction(&program_id, &supporter_b.pubkey(), &bucket, &main_bucket, &escrow_b, SIDE_B, 0);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SideAlreadyInControl), "B already controls the bucket");
+
+        println!("✓ Deposit and flush is atomic");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_swap_guard_rejects_stale_deposit() {
+        println!("\n=== Testing: Swap Guard Rejects Stale Deposits ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B flips first
+        let ix = deposit_and_flush_instruction(&program_id, &supporter_b.pubkey(), &bucket, &main_bucket, &escrow_b, SIDE_B, 0);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // A's supporter built their deposit against the original state (last_swap 1 SOL, A in control)
+        let escrow_a_before = client.get_balance(&escrow_a).unwrap();
+        let mut ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 1_050_000_000);
+        ix.data.extend_from_slice(&swap_guard_data(Some(1_000_000_000), Some(&address_a), None));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::LastSwapMismatch), "Stale deposit should be rejected");
+        assert_eq!(client.get_balance(&escrow_a).unwrap(), escrow_a_before, "Rejected deposit must not reach the escrow");
+
+        // The auto-computed deposit is capped by max_lamports
+        let mut ix = deposit_and_flush_instruction(&program_id, &supporter_a.pubkey(), &bucket, &main_bucket, &escrow_a, SIDE_A, 0);
+        ix.data.extend_from_slice(&swap_guard_data(None, Some(&address_b), Some(1_000_000_000)));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::MaxLamportsExceeded), "Deposit above max_lamports should be rejected");
+
+        println!("✓ Swap guard rejected stale and oversized deposits");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_reinforce_raises_threshold() {
+        println!("\n=== Testing: Reinforce Raises the Threshold Without Flipping ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Bucket starts with A in control, threshold 1.05 SOL
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // A defends preemptively with 2 SOL
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 2_000_000_000);
+        let reinforce_ix = reinforce_instruction(&program_id, &bucket, &main_bucket, &escrow_a, SIDE_A);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, reinforce_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        assert!(client.get_balance(&main_bucket).unwrap() >= 2_000_000_000, "Main bucket should hold the reinforcement");
+
+        // The challenger cannot reinforce
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let reinforce_ix = reinforce_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, reinforce_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SideNotInControl), "Only the side in control can reinforce");
+
+        // The old 1.05 SOL threshold no longer flips: B now needs 2.1 SOL
+        let ix = deposit_and_flush_instruction(&program_id, &supporter_b.pubkey(), &bucket, &main_bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::InsufficientEscrowBalance), "Flip below the reinforced threshold should fail");
+
+        // A reinforced bucket holds funds, so the creator can no longer close it
+        let close_ix = close_bucket_instruction(&program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, &[]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::BucketHasFlips), "Close should fail after a reinforcement");
+
+        println!("✓ Reinforce raised the threshold and kept A in control");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_three_sided_bucket() {
+        println!("\n=== Testing: Three-Sided Bucket ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let address_c = Keypair::new().pubkey();
+        let supporter_c = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_c.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let (escrow_c, _) = derive_escrow_pda(&program_id, &bucket, 2);
+
+        // Short slot delay so the claim can be tested right away
+        let create_ix = with_extra_sides(
+            create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            ),
+            &[address_c],
+            &[escrow_c],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Side C deposits and flips control to itself
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_c.pubkey(), &bucket, &escrow_c, 2, 1_050_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_c, 2);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_c.pubkey()), &[&supporter_c], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // A claim that leaves out escrow C is rejected
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_c, &creator.pubkey(), &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(instruction_error(client.send_and_confirm_transaction(&tx)), Some(InstructionError::NotEnoughAccountKeys), "Claim must include every escrow");
+
+        // Side C wins and every escrow is closed
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_c, &creator.pubkey(), &[escrow_c],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        assert!(client.get_balance(&address_c).unwrap() > 0, "Side C should receive the pot");
+        assert!(client.get_account(&escrow_c).is_err(), "Escrow C should be closed");
+        println!("✓ Three-sided bucket paid the side in control");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_deposit_records() {
+        println!("\n=== Testing: Deposit Records ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let (record, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_B, &supporter_b.pubkey());
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Two deposits to B: the first creates the record, the second updates it
+        for amount in [600_000_000, 450_000_000] {
+            let ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, amount);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).unwrap();
+        }
+
+        let data = client.get_account(&record).unwrap().data;
+        let receipt = DepositRecord::decode(&data).unwrap();
+        assert_eq!(receipt.depositor, supporter_b.pubkey().to_bytes());
+        assert_eq!(receipt.side, SIDE_B);
+        assert_eq!(receipt.total_contributed, 1_050_000_000);
+        assert_eq!(receipt.pending, 1_050_000_000);
+        assert_eq!(receipt.total_flushed, 0);
+
+        // B's escrow was swept once since the last deposit: everything pending counts as flushed
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        assert_eq!(receipt.flushed_and_pending(receipt.flush_count + 1), Some((1_050_000_000, 0)));
+        println!("✓ Deposit record tracked contributions");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_deposit_into_wrong_account_rejected() {
+        println!("\n=== Testing: Deposit Into Wrong Account Is Rejected ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let depositor = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &depositor.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Main bucket, the bucket itself and the other side's escrow are all program-owned,
+        // but none of them is the escrow for the selected side
+        for (target, side) in [(&main_bucket, SIDE_A), (&bucket, SIDE_A), (&escrow_a, SIDE_B)] {
+            let deposit_ix = deposit_to_escrow_instruction(&program_id, &depositor.pubkey(), &bucket, target, side, 1_000_000);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&depositor.pubkey()), &[&depositor], recent_blockhash);
+            assert_eq!(
+                program_error(client.send_and_confirm_transaction(&tx)),
+                Some(HateFunError::invalid_escrow_for_side(side)),
+                "Deposit into {} should fail",
+                target,
+            );
+        }
+        println!("✓ Correctly rejected deposits outside the selected side's escrow");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_claim_payout_after_slot_delay() {
+        println!("\n=== Testing: Claim Payout After Slot-Based Delay ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Shortest allowed window: 150 slots (~1 minute)
+        let claim_delay_slots = 150;
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed,
+            DELAY_UNIT_SLOTS, claim_delay_slots, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B takes control
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        let claim_ix = || claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+
+        // Too early: the window has not elapsed yet
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::ClaimTooEarly), "Claim should fail before the delay");
+
+        println!("Waiting for {} slots...", claim_delay_slots);
+        while client.get_slot().unwrap() < flip_slot + claim_delay_slots + 1 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // Rent reserves of the four closed PDAs go back to the payer, outside the pot
+        let bucket_len = client.get_account(&bucket).unwrap().data.len();
+        let rent_reserves = client.get_minimum_balance_for_rent_exemption(bucket_len).unwrap()
+            + 3 * client.get_minimum_balance_for_rent_exemption(0).unwrap();
+        let creator_before = client.get_balance(&creator.pubkey()).unwrap();
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Claim failed");
+
+        // Pot is exactly the flipping deposit: 5% creator fee, 0.5% claimer fee
+        assert_eq!(client.get_balance(&address_b).unwrap(), 1_039_500_000, "Winner B should be paid the pot minus fees");
+        assert_eq!(
+            client.get_balance(&creator.pubkey()).unwrap(),
+            creator_before + 55_000_000 + rent_reserves,
+            "Creator (the payer) should receive the fee and the rent reserves"
+        );
+        assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+        assert!(client.get_account(&escrow_a).is_err(), "Escrow A should be closed");
+        println!("✓ Payout claimed after slot-based delay");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_claim_payout_after_seconds_delay() {
+        println!("\n=== Testing: Claim Payout After Seconds-Based Delay ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Shortest allowed window: 60 seconds of cluster time
+        let claim_delay_seconds = 60;
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed,
+            DELAY_UNIT_SECONDS, claim_delay_seconds, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B takes control; the flip records the cluster's unix timestamp
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.claim_delay_unit, DELAY_UNIT_SECONDS);
+        assert!(state.last_flip_timestamp > 0, "Flip should record the unix timestamp");
+        let deadline = state.last_flip_timestamp + claim_delay_seconds as i64;
+
+        let claim_ix = || claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+
+        // Too early: the cluster clock has not reached the deadline yet
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::ClaimTooEarly), "Claim should fail before the delay");
+        assert!(client.get_account(&bucket).is_ok(), "Bucket stays open before the deadline");
+
+        // Wait on the cluster's block time, not the host clock: the program compares Clock::unix_timestamp
+        println!("Waiting for cluster time {}...", deadline);
+        loop {
+            let slot = client.get_slot().unwrap();
+            if client.get_block_time(slot).is_ok_and(|block_time| block_time > deadline) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Claim failed");
+
+        assert_eq!(client.get_balance(&address_b).unwrap(), 1_039_500_000, "Winner B should be paid the pot minus fees");
+        assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+        println!("✓ Payout claimed after seconds-based delay");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_validation_fees_too_high() {
+        println!("\n=== Testing: Validation - Fees Too High ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let payer = Keypair::new();
+
+        airdrop_if_needed(&client, &payer.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &payer.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Try to create with 21% total fees (should fail)
+        let ix = create_bucket_instruction(
+            &program_id, &payer.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &payer.pubkey(),
+            1600, // 16%
+            500,  // 5% = 21% total
+            1_000_000_000, 500, &seed, DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let result = client.send_and_confirm_transaction(&tx);
+
+        assert_eq!(program_error(result), Some(HateFunError::FeesTooHigh), "Transaction should fail with fees > 20%");
+        println!("✓ Correctly rejected fees > 20%");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_refund_pending_after_claim() {
+        println!("\n=== Testing: Refund Pending After Claim ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = with_bucket_options(
+            create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            ),
+            &[REFUND_POLICY_REFUND],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B takes control; A's supporter deposits but never reaches the threshold
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        let pending_deposit = 400_000_000;
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, pending_deposit);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Refunds are rejected while the game is still running
+        let refund_ix = || refund_pending_instruction(&program_id, &bucket, &escrow_a, SIDE_A, &supporter_a.pubkey());
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[refund_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::BucketNotSettled), "Refund must wait for the claim");
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // The claim pays out the main bucket only; escrow A keeps the unflushed deposit
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        assert!(client.get_account(&bucket).is_ok(), "Bucket stays open for refunds");
+        assert!(client.get_balance(&escrow_a).unwrap() > pending_deposit, "Escrow A keeps the pending deposit");
+
+        // The bucket cannot be closed while a refund is still owed
+        let close_ix = || close_settled_instruction(&program_id, &bucket, &creator.pubkey(), &escrow_a, &escrow_b, &[]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SettlementNotDrained), "Close must wait for the refund");
+
+        // Anyone (here the claimer) can push the refund back to the depositor
+        let balance_before = client.get_balance(&supporter_a.pubkey()).unwrap();
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[refund_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Refund failed");
+        assert_eq!(client.get_balance(&supporter_a.pubkey()).unwrap(), balance_before + pending_deposit);
+
+        let (record_address, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_A, &supporter_a.pubkey());
+        let record = DepositRecord::decode(&client.get_account(&record_address).unwrap().data).unwrap();
+        assert_eq!(record.pending, 0);
+        assert_eq!(record.total_refunded, pending_deposit);
+
+        // A second refund has nothing left to return
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[refund_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::NothingToRefund), "Nothing left to refund");
+
+        // Once drained, anyone can close the bucket and escrows back to the payer
+        let payer_before = client.get_balance(&creator.pubkey()).unwrap();
+        let remaining: u64 = [bucket, escrow_a, escrow_b].iter().map(|account| client.get_balance(account).unwrap_or(0)).sum();
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Close settled failed");
+        assert_eq!(client.get_balance(&creator.pubkey()).unwrap(), payer_before + remaining);
+        for account in [bucket, escrow_a, escrow_b] {
+            assert_eq!(client.get_balance(&account).unwrap_or(0), 0, "Settled accounts should be closed");
+        }
+        println!("✓ Pending deposit refunded after the claim");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_recreated_bucket_resets_deposit_records() {
+        println!("\n=== Testing: Re-Created Bucket Resets Deposit Records ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let (record_address, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_A, &supporter_a.pubkey());
+
+        let create_ix = || create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+        );
+        let close_ix = || close_bucket_instruction(&program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, &[]);
+
+        // A bucket cannot be closed in its creation slot, so every bucket at this address gets its own creation slot
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix(), close_ix()], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::BucketTooNew), "Close in the creation slot should fail");
+
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix()], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B takes control; A's supporter leaves a pending deposit behind
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 400_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let old_creation_slot = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap().creation_slot;
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // The sweeping claim closes the bucket; A's record still shows the swept deposit as pending
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+        let stale = DepositRecord::decode(&client.get_account(&record_address).unwrap().data).unwrap();
+        assert_eq!(stale.pending, 400_000_000);
+        assert_eq!(stale.bucket_creation_slot, old_creation_slot);
+
+        // Same creator and seed: the new bucket lives at the same address with a later creation slot
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix()], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Re-create failed");
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert!(state.creation_slot > old_creation_slot);
+
+        // The stale record cannot draw its old pending balance out of the new escrow
+        let seed_deposit = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_a, SIDE_A, 500_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[seed_deposit], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let withdraw_ix = withdraw_pending_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 0);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::InvalidDepositRecord), "Stale record must not withdraw");
+
+        // The first deposit to the new bucket starts the record over
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 2_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        let record = DepositRecord::decode(&client.get_account(&record_address).unwrap().data).unwrap();
+        assert_eq!(record.bucket_creation_slot, state.creation_slot);
+        assert_eq!(record.total_contributed, 2_000);
+        assert_eq!(record.pending, 2_000);
+        assert_eq!(record.total_flushed, 0);
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.deposit_record_count, 2);
+        println!("✓ Deposit record reset for the re-created bucket");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_withdraw_pending() {
+        println!("\n=== Testing: Withdraw Pending Deposit ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 2_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed,
+            DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B deposits toward a flip that never happens
+        let deposit = 500_000_000;
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, deposit);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Asking for more than the receipt is rejected
+        let withdraw_ix = withdraw_pending_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, deposit + 1);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::WithdrawTooLarge), "Withdrawal above the receipt must fail");
+
+        // Partial withdrawal, then the rest with amount 0
+        for amount in [200_000_000, 0] {
+            let withdraw_ix = withdraw_pending_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, amount);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).expect("Withdrawal failed");
+        }
+
+        let (record_address, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_B, &supporter_b.pubkey());
+        let record = DepositRecord::decode(&client.get_account(&record_address).unwrap().data).unwrap();
+        assert_eq!(record.pending, 0);
+        assert_eq!(record.total_withdrawn, deposit);
+
+        let escrow_rent = client.get_minimum_balance_for_rent_exemption(0).unwrap();
+        assert_eq!(client.get_balance(&escrow_b).unwrap(), escrow_rent, "Escrow keeps only its reserve");
+        println!("✓ Pending deposit withdrawn before any flush");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_close_deposit_record() {
+        println!("\n=== Testing: Close Deposit Record ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 2_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &Keypair::new().pubkey(), &Keypair::new().pubkey(), &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed,
+            DELAY_UNIT_EPOCHS, 3, 1_000, 100_000,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let creation_slot = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap().creation_slot;
+
+        // Both sides get a pending deposit
+        for (supporter, escrow, side) in [(&supporter_a, &escrow_a, SIDE_A), (&supporter_b, &escrow_b, SIDE_B)] {
+            let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter.pubkey(), &bucket, escrow, side, 300_000_000);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter.pubkey()), &[supporter], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).unwrap();
+        }
+
+        // The creator pays the fees so the depositors' balances only move by what the program sends
+        let close_record = |supporter: &Keypair, side: u8| {
+            let close_ix = close_deposit_record_instruction(&program_id, &supporter.pubkey(), &bucket, side);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&creator.pubkey()), &[&creator, supporter], recent_blockhash);
+            program_error(client.send_and_confirm_transaction(&tx))
+        };
+
+        // A record with a pending deposit cannot be closed
+        let (record_b, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_B, &supporter_b.pubkey());
+        let record_rent = client.get_balance(&record_b).unwrap();
+        let tx_failed = close_record(&supporter_b, SIDE_B);
+        assert_eq!(tx_failed, Some(HateFunError::DepositRecordInUse), "Pending deposit must be withdrawn first");
+
+        // Once the deposit is withdrawn, the empty record closes and its rent goes back
+        for (supporter, escrow, side) in [(&supporter_a, &escrow_a, SIDE_A), (&supporter_b, &escrow_b, SIDE_B)] {
+            let withdraw_ix = withdraw_pending_instruction(&program_id, &supporter.pubkey(), &bucket, escrow, side, 0);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&supporter.pubkey()), &[supporter], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).expect("Withdrawal failed");
+        }
+        let balance_before = client.get_balance(&supporter_b.pubkey()).unwrap();
+        assert_eq!(close_record(&supporter_b, SIDE_B), None);
+        assert!(client.get_account(&record_b).is_err(), "Record should be closed");
+        assert_eq!(client.get_balance(&supporter_b.pubkey()).unwrap(), balance_before + record_rent);
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.deposit_record_count, 1, "Only A's record is left");
+
+        // A record outlives its closed bucket until the depositor closes it
+        while client.get_slot().unwrap() <= creation_slot {
+            std::thread::sleep(std::time::Duration::from_millis(400));
+        }
+        let close_ix = close_bucket_instruction(&program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, &[]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Close failed");
+
+        let (record_a, _) = derive_deposit_record_pda(&program_id, &bucket, SIDE_A, &supporter_a.pubkey());
+        assert!(client.get_account(&record_a).is_ok());
+        assert_eq!(close_record(&supporter_a, SIDE_A), None);
+        assert!(client.get_account(&record_a).is_err(), "Stale record should be closed");
+        println!("✓ Empty and stale deposit records closed by their depositors");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_proportional_payout() {
+        println!("\n=== Testing: Proportional Payout ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_1 = Keypair::new();
+        let supporter_2 = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_1.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &supporter_2.pubkey(), 2_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = with_bucket_options(
+            create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            ),
+            &[REFUND_POLICY_SWEEP, 0, PAYOUT_MODE_PROPORTIONAL],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Two B supporters contribute 3:1 and flip control to B
+        let deposit_1 = deposit_to_escrow_instruction(&program_id, &supporter_1.pubkey(), &bucket, &escrow_b, SIDE_B, 900_000_000);
+        let deposit_2 = deposit_to_escrow_instruction(&program_id, &supporter_2.pubkey(), &bucket, &escrow_b, SIDE_B, 300_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_1, deposit_2, flush_ix],
+            Some(&supporter_1.pubkey()),
+            &[&supporter_1, &supporter_2],
+            recent_blockhash,
+        );
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // The claim pays the fees; the winner's cut stays in the bucket for the contributors
+        let winner_before = client.get_balance(&address_b).unwrap_or(0);
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        assert_eq!(client.get_balance(&address_b).unwrap_or(0), winner_before, "Winner address is not paid directly");
+
+        // The bucket cannot be closed while shares are still unclaimed
+        let close_ix = || close_settled_instruction(&program_id, &bucket, &creator.pubkey(), &escrow_a, &escrow_b, &[]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SettlementNotDrained), "Close must wait for the shares");
+
+        let mut shares = Vec::new();
+        for supporter in [&supporter_1, &supporter_2] {
+            let balance_before = client.get_balance(&supporter.pubkey()).unwrap();
+            let share_ix = claim_share_instruction(&program_id, &bucket, SIDE_B, &supporter.pubkey());
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[share_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).expect("Share claim failed");
+            shares.push(client.get_balance(&supporter.pubkey()).unwrap() - balance_before);
+        }
+
+        // 3:1 split of the winner's cut, the last claimer taking any dust
+        let winner_cut = shares[0] + shares[1];
+        assert_eq!(shares[0], winner_cut * 3 / 4, "Shares should follow contributions");
+
+        // A second claim of the same record is rejected
+        let share_ix = claim_share_instruction(&program_id, &bucket, SIDE_B, &supporter_1.pubkey());
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[share_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::ShareAlreadyClaimed), "Share already claimed");
+
+        // Once every share is paid, the bucket's rent goes back to the payer
+        let payer_before = client.get_balance(&creator.pubkey()).unwrap();
+        let remaining: u64 = [bucket, escrow_a, escrow_b].iter().map(|account| client.get_balance(account).unwrap_or(0)).sum();
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix()], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Close settled failed");
+        assert_eq!(client.get_balance(&creator.pubkey()).unwrap(), payer_before + remaining);
+        assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+        println!("✓ Winner's cut shared pro rata among B contributors");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_deposit_with_claim_earns_no_share() {
+        println!("\n=== Testing: Deposit Sent With the Claim Earns No Share ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let supporter_a = Keypair::new();
+        let supporter_b = Keypair::new();
+        let attacker = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_a.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 2_000_000_000);
+        airdrop_if_needed(&client, &attacker.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        let create_ix = with_bucket_options(
+            create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            ),
+            &[REFUND_POLICY_SWEEP, 0, PAYOUT_MODE_PROPORTIONAL],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B flips with a real contribution; A leaves a losing pending deposit behind
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_a.pubkey(), &bucket, &escrow_a, SIDE_A, 800_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_a.pubkey()), &[&supporter_a], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        // The attacker deposits into the winning escrow and claims in the same transaction
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &attacker.pubkey(), &bucket, &escrow_b, SIDE_B, 3_000_000_000);
+        let claim_ix = claim_payout_instruction(
+            &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &creator.pubkey(), &attacker.pubkey(), &address_b, &creator.pubkey(), &[],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, claim_ix], Some(&attacker.pubkey()), &[&attacker], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Deposit and claim failed");
+
+        // Only B's flushed contribution carries share weight
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.unclaimed_contributions, 1_100_000_000);
+
+        let share_ix = claim_share_instruction(&program_id, &bucket, SIDE_B, &attacker.pubkey());
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[share_ix], Some(&attacker.pubkey()), &[&attacker], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::NoFlushedContribution), "Swept deposit must not earn a share");
+
+        // B's supporter takes the whole share pool
+        let share_pool = state.share_pool;
+        let balance_before = client.get_balance(&supporter_b.pubkey()).unwrap();
+        let share_ix = claim_share_instruction(&program_id, &bucket, SIDE_B, &supporter_b.pubkey());
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[share_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Share claim failed");
+        assert_eq!(client.get_balance(&supporter_b.pubkey()).unwrap(), balance_before + share_pool);
+        println!("✓ Deposit sent with the claim went to the pot without a share");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_side_config_locked_after_deposit() {
+        println!("\n=== Testing: Side Config Locked After a Deposit ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 1_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Withdrawals disabled: B's supporters could not leave a redirected side
+        let create_ix = with_bucket_options(
+            create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            ),
+            &[REFUND_POLICY_SWEEP, 1],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // Outside the CreateBucket transaction the config is rejected even before any deposit
+        let config_ix = create_side_config_instruction(&program_id, &creator.pubkey(), &bucket, SIDE_A, &[(address_a, 10_000)]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[config_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SideConfigLocked), "Side configs are only accepted in the creation slot");
+
+        // A pending deposit into B, below the flip threshold
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 100_000_000);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // The creator can no longer redirect B's winnings to their own wallet
+        let config_ix = create_side_config_instruction(&program_id, &creator.pubkey(), &bucket, SIDE_B, &[(creator.pubkey(), 10_000)]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[config_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SideConfigLocked), "Side configs are locked once a deposit is recorded");
+        println!("✓ Side config rejected after a deposit");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_side_config_on_recreated_bucket() {
+        println!("\n=== Testing: Side Config On a Re-Created Bucket ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let charities = [Keypair::new().pubkey(), Keypair::new().pubkey()];
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let (side_config_b, _) = derive_side_config_pda(&program_id, &bucket, SIDE_B);
+
+        let create_with_config = |recipients: &[(Pubkey, u16)]| {
+            let create_ix = create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            );
+            let config_ix = create_side_config_instruction(&program_id, &creator.pubkey(), &bucket, SIDE_B, recipients);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[create_ix, config_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).is_ok()
+        };
+
+        assert!(create_with_config(&[(charities[0], 10_000)]), "Create with side config failed");
+        let creation_slot = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap().creation_slot;
+
+        // Closing needs the side config too and closes it with the bucket
+        while client.get_slot().unwrap() <= creation_slot {
+            std::thread::sleep(std::time::Duration::from_millis(400));
+        }
+        let close_ix = close_bucket_instruction(&program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, &[]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(std::slice::from_ref(&close_ix), Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(instruction_error(client.send_and_confirm_transaction(&tx)), Some(InstructionError::NotEnoughAccountKeys), "Close must include the side configs");
+
+        let close_ix = with_closed_side_configs(close_ix, &[side_config_b]);
+        let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Close failed");
+        assert!(client.get_account(&side_config_b).is_err(), "Side config should be closed");
+
+        // The re-created bucket sets a fresh config with its own recipients
+        assert!(create_with_config(&[(charities[1], 10_000)]), "Side config on a re-created bucket failed");
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        let config = client.get_account(&side_config_b).unwrap().data;
+        let slot_offset = core::mem::offset_of!(SideConfig, bucket_creation_slot);
+        let recipients_offset = core::mem::offset_of!(SideConfig, recipients);
+        assert_eq!(config[slot_offset..][..8], state.creation_slot.to_le_bytes());
+        assert_eq!(config[recipients_offset..][..32], charities[1].to_bytes());
+        assert_eq!(config[recipients_offset + 32..][..32], [0u8; 32], "Old recipients are cleared");
+        println!("✓ Side config set again on the re-created bucket");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_coalition_side_payout() {
+        println!("\n=== Testing: Weighted Coalition Payout ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let address_b = Keypair::new().pubkey();
+        let charities = [Keypair::new().pubkey(), Keypair::new().pubkey(), Keypair::new().pubkey()];
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+        let (side_config_b, _) = derive_side_config_pda(&program_id, &bucket, SIDE_B);
+
+        // Side B is a coalition of three charities splitting 50/30/20
+        let create_ix = create_bucket_instruction(
+            &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+            &address_a, &address_b, &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+        );
+        let config_ix = create_side_config_instruction(
+            &program_id, &creator.pubkey(), &bucket, SIDE_B,
+            &[(charities[0], 5_000), (charities[1], 3_000), (charities[2], 2_000)],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[create_ix, config_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+
+        // B takes control; the config can no longer change
+        let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+        let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).unwrap();
+        let flip_slot = client.get_slot().unwrap();
+
+        let config_ix = create_side_config_instruction(&program_id, &creator.pubkey(), &bucket, SIDE_A, &[(address_a, 10_000)]);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[config_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::SideConfigLocked), "Side configs are locked after the first flip");
+
+        println!("Waiting for 150 slots...");
+        while client.get_slot().unwrap() < flip_slot + 151 {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+
+        let claim_ix = with_side_config(
+            claim_payout_instruction(
+                &program_id, &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &creator.pubkey(), &claimer.pubkey(), &address_b, &creator.pubkey(), &[],
+            ),
+            &side_config_b,
+            &charities,
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[claim_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Claim failed");
+
+        let cuts: Vec<u64> = charities.iter().map(|charity| client.get_balance(charity).unwrap()).collect();
+        assert_eq!(cuts[1], (cuts[0] + cuts[1] + cuts[2]) * 3 / 10, "Second charity receives 30%");
+        assert_eq!(client.get_balance(&address_b).unwrap_or(0), 0, "Coalition address itself is not paid");
+        println!("✓ Winner's cut split 50/30/20 among the coalition");
+
+        // The bucket stays Settled until Close Settled closes it together with the side config
+        let state = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap();
+        assert_eq!(state.status, BucketStatus::Settled as u8);
+        let config_rent = client.get_balance(&side_config_b).unwrap();
+        let creator_before = client.get_balance(&creator.pubkey()).unwrap();
+        let close_ix = with_closed_side_configs(
+            close_settled_instruction(&program_id, &bucket, &creator.pubkey(), &escrow_a, &escrow_b, &[]),
+            &[creator.pubkey(), side_config_b],
+        );
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[close_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Close settled failed");
+        assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+        assert!(client.get_account(&side_config_b).is_err(), "Side config should be closed");
+        assert!(client.get_balance(&creator.pubkey()).unwrap() >= creator_before + config_rent, "Creator gets the config rent back");
+        println!("✓ Side config closed with the settled bucket");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_finalize_then_withdraw() {
+        println!("\n=== Testing: Finalize Then Withdraw ===\n");
+
+        let client = setup_client();
+        let program_id = get_program_id();
+        let creator = Keypair::new();
+        let claimer = Keypair::new();
+        let address_a = Keypair::new().pubkey();
+        let winner_b = Keypair::new();
+        let supporter_b = Keypair::new();
+
+        airdrop_if_needed(&client, &creator.pubkey(), 5_000_000_000);
+        airdrop_if_needed(&client, &claimer.pubkey(), 1_000_000_000);
+        airdrop_if_needed(&client, &winner_b.pubkey(), 100_000_000);
+        airdrop_if_needed(&client, &supporter_b.pubkey(), 5_000_000_000);
+
+        let seed: [u8; 32] = rand::random();
+        let (bucket, _) = derive_bucket_pda(&program_id, &creator.pubkey(), &seed);
+        let (main_bucket, _) = derive_main_bucket_pda(&program_id, &bucket);
+        let (escrow_a, _) = derive_escrow_a_pda(&program_id, &bucket);
+        let (escrow_b, _) = derive_escrow_b_pda(&program_id, &bucket);
+
+        // Create, flip to B, wait out the delay and finalize; returns the settlement
+        let play_and_finalize = || {
+            let create_ix = create_bucket_instruction(
+                &program_id, &creator.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b,
+                &address_a, &winner_b.pubkey(), &creator.pubkey(), 500, 50, 1_000_000_000, 500, &seed, DELAY_UNIT_SLOTS, 150, 1_000, 100_000,
+            );
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[create_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).unwrap();
+            let creation_slot = Bucket::decode(&client.get_account(&bucket).unwrap().data).unwrap().creation_slot;
+
+            let deposit_ix = deposit_to_escrow_instruction(&program_id, &supporter_b.pubkey(), &bucket, &escrow_b, SIDE_B, 1_100_000_000);
+            let flush_ix = flush_escrow_instruction(&program_id, &bucket, &main_bucket, &escrow_b, SIDE_B);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[deposit_ix, flush_ix], Some(&supporter_b.pubkey()), &[&supporter_b], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).unwrap();
+            let flip_slot = client.get_slot().unwrap();
+
+            println!("Waiting for 150 slots...");
+            while client.get_slot().unwrap() < flip_slot + 151 {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+            }
+
+            // Finalize freezes the split without touching creator or winner accounts
+            let finalize_ix = finalize_instruction(&program_id, &claimer.pubkey(), &bucket, &main_bucket, &escrow_a, &escrow_b, creation_slot, &[]);
+            let recent_blockhash = client.get_latest_blockhash().unwrap();
+            let tx = Transaction::new_signed_with_payer(&[finalize_ix], Some(&claimer.pubkey()), &[&claimer], recent_blockhash);
+            client.send_and_confirm_transaction(&tx).expect("Finalize failed");
+            assert!(client.get_account(&bucket).is_err(), "Bucket should be closed");
+            derive_settlement_pda(&program_id, &bucket, creation_slot).0
+        };
+
+        let settlement = play_and_finalize();
+        let relayer = &claimer;
+
+        // A destination equal to the settlement is rejected
+        let withdraw_ix = withdraw_instruction(&program_id, &winner_b.pubkey(), &settlement, &claimer.pubkey(), Some(&settlement));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&winner_b.pubkey()), &[&winner_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::InvalidDestination), "Withdraw to the settlement should fail");
+
+        // The winner pulls their share to a destination of their choice
+        let destination = Keypair::new().pubkey();
+        let withdraw_ix = withdraw_instruction(&program_id, &winner_b.pubkey(), &settlement, &claimer.pubkey(), Some(&destination));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&winner_b.pubkey()), &[&winner_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Winner withdraw failed");
+        assert!(client.get_balance(&destination).unwrap() > 1_000_000_000, "Winner's cut should arrive");
+
+        // Each entry can only be withdrawn once
+        let withdraw_ix = withdraw_instruction(&program_id, &winner_b.pubkey(), &settlement, &claimer.pubkey(), Some(&destination));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&winner_b.pubkey()), &[&winner_b], recent_blockhash);
+        assert_eq!(program_error(client.send_and_confirm_transaction(&tx)), Some(HateFunError::NothingToCollect), "Second withdraw should fail");
+
+        // The same creator and seed play again while the first settlement still holds entries;
+        // the new bucket finalizes into a settlement of its own
+        let second_settlement = play_and_finalize();
+        assert_ne!(second_settlement, settlement);
+        assert!(client.get_account(&settlement).is_ok(), "First settlement keeps its entries");
+        let withdraw_ix = withdraw_instruction(&program_id, &winner_b.pubkey(), &second_settlement, &claimer.pubkey(), Some(&destination));
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&winner_b.pubkey()), &[&winner_b], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Winner withdraw from the second settlement failed");
+
+        // Anyone pushes the creator's entries (fee and rent refund) to the creator without their signature
+        let creator_before = client.get_balance(&creator.pubkey()).unwrap();
+        let withdraw_ix = withdraw_instruction(&program_id, &creator.pubkey(), &settlement, &claimer.pubkey(), None);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&relayer.pubkey()), &[relayer], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Creator withdraw failed");
+        assert!(client.get_balance(&creator.pubkey()).unwrap() > creator_before, "Creator fee should arrive");
+
+        // The last entry closes the settlement: the claimer gets their fee and the settlement's rent
+        let settlement_balance = client.get_balance(&settlement).unwrap();
+        let claimer_before = client.get_balance(&claimer.pubkey()).unwrap();
+        let withdraw_ix = withdraw_instruction(&program_id, &claimer.pubkey(), &settlement, &claimer.pubkey(), None);
+        let recent_blockhash = client.get_latest_blockhash().unwrap();
+        let tx = Transaction::new_signed_with_payer(&[withdraw_ix], Some(&creator.pubkey()), &[&creator], recent_blockhash);
+        client.send_and_confirm_transaction(&tx).expect("Claimer withdraw failed");
+        assert_eq!(client.get_balance(&claimer.pubkey()).unwrap(), claimer_before + settlement_balance);
+        assert!(client.get_account(&settlement).is_err(), "Settlement should be closed");
+        println!("✓ Finalized settlement withdrawn by each payee");
     }
 }
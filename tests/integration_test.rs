@@ -9,7 +9,9 @@
 //
 // For now, we'll create unit tests for the validation logic and helper functions.
 
+// The tests restate the program's bounds as literal comparisons
 #[cfg(test)]
+#[allow(unused_imports, clippy::assertions_on_constants, clippy::nonminimal_bool)]
 mod tests {
     use super::*;
 